@@ -1,10 +1,40 @@
-use crate::globals::{ENV_SLACK_CHANNEL, ENV_SLACK_TOKEN};
-use clap::{builder::Styles, Command, Parser};
+use crate::output::Output;
+use crate::time;
+use clap::builder::{RangedU64ValueParser, Styles};
+use clap::{Args, Command, Parser, Subcommand};
 use clap_complete::aot::Shell;
 use clap_complete::aot::{generate, Generator};
 use env::VarError;
+use reqwest::StatusCode;
+use slack_message::globals::{
+  ENV_SLACK_API_BASE, ENV_SLACK_CHANNEL, ENV_SLACK_TOKEN, ENV_SLACK_WEBHOOK,
+};
+use slack_message::slack::attachment::{self, Attachment, Field};
+use slack_message::slack::blocks::{self, Block};
+use slack_message::slack::conversations::Range;
+use slack_message::slack::reactions;
+use slack_message::slack::{ApiError, Message, Retry, SlackError};
 use std::env;
-use std::io;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io::{self, IsTerminal, Read};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+pub const EXIT_API: u8 = 1;
+pub const EXIT_USAGE: u8 = 2;
+pub const EXIT_CONFIG: u8 = 3;
+pub const EXIT_TRANSPORT: u8 = 4;
+pub const EXIT_STATUS: u8 = 5;
+pub const EXIT_DECODE: u8 = 6;
+pub const EXIT_INPUT: u8 = 7;
+pub const EXIT_PARTIAL: u8 = 8;
+pub const EXIT_AUTH: u8 = 10;
+pub const EXIT_CHANNEL: u8 = 11;
+pub const EXIT_RATE_LIMITED: u8 = 12;
+pub const EXIT_MESSAGE: u8 = 13;
+pub const EXIT_USER: u8 = 14;
 
 #[derive(Parser, Debug, Default)]
 #[command(
@@ -16,29 +46,574 @@ use std::io;
   version,
 )]
 pub struct Cli {
-  #[arg(short, long)]
+  #[arg(
+    long,
+    value_name = "URL",
+    global = true,
+    help = "Base url of a slack compatible api to call instead, ex) 'http://localhost:8080/api'"
+  )]
+  pub api_base: Option<String>,
+  #[arg(
+    long,
+    value_parser = time::parse_time,
+    help = "Schedule the message for unix seconds or RFC 3339, ex) '2025-01-31T17:00:00Z'"
+  )]
+  pub at: Option<i64>,
+  #[arg(
+    long,
+    value_name = "PATH",
+    conflicts_with_all = [
+      "at", "delay", "ephemeral_to", "blocks", "blocks_file", "color", "fields",
+      "footer", "pretext", "title",
+    ],
+    help = "Upload a file with the message as its comment, can be repeated."
+  )]
+  pub attach: Vec<PathBuf>,
+  #[arg(short, long, global = true)]
   auth_token: Option<String>,
-  #[arg(short, long)]
-  pub channel: Option<String>,
+  #[command(flatten)]
+  pub body: MessageArgs,
+  #[arg(short, long, global = true, value_delimiter = ',')]
+  pub channel: Vec<String>,
+  #[command(subcommand)]
+  pub command: Option<Commands>,
   #[arg(long)]
   pub completion: Option<Shell>,
-  #[arg(short, long)]
+  #[arg(
+    long,
+    value_name = "USER",
+    conflicts_with_all = ["at", "delay"],
+    help = "Only show the message to this user id in the channel. ex) 'U12345ABCDE'"
+  )]
+  pub ephemeral_to: Option<String>,
+  #[arg(
+    long = "in",
+    value_name = "DURATION",
+    conflicts_with = "at",
+    value_parser = time::parse_duration,
+    help = "Schedule the message this far from now, ex) '2h' or '1h30m'."
+  )]
+  pub delay: Option<Duration>,
+  #[arg(short, long, value_parser = parse_icon)]
   pub icon: Option<String>,
-  pub message: String,
-  #[arg(short, long, help="Timestamp of message for which to reply. ex) '1734376519.228539'")]
+  #[arg(
+    long,
+    value_name = "N",
+    global = true,
+    help = "Retries for rate limited, 5xx and failed connections, 0 to turn off. [default: 3]"
+  )]
+  pub max_retries: Option<u32>,
+  #[arg(
+    short,
+    long,
+    value_enum,
+    default_value_t,
+    global = true,
+    help = "How results are printed, 'json' is slack's response and 'ts-only' the bare timestamp."
+  )]
+  pub output: Output,
+  #[arg(
+    long,
+    conflicts_with_all = ["at", "attach", "delay", "ephemeral_to", "webhook_url"],
+    help = "Also print the message's url, needs a token."
+  )]
+  pub permalink: bool,
+  #[arg(
+    short,
+    long,
+    help = "Timestamp of message for which to reply. ex) '1734376519.228539'"
+  )]
   pub timestamp: Option<String>,
+  #[arg(
+    long,
+    value_name = "DURATION",
+    global = true,
+    value_parser = time::parse_duration,
+    help = "Give up on a call after this long, retries included, ex) '30s'. [default: 1m]"
+  )]
+  pub timeout: Option<Duration>,
+  #[arg(
+    long,
+    value_name = "USER",
+    global = true,
+    conflicts_with = "channel",
+    help = "Direct message a user by email, @handle or id instead of a channel, repeat for a group dm."
+  )]
+  pub to_user: Vec<String>,
   #[arg(short, long)]
   pub username: Option<String>,
+  #[arg(
+    long,
+    value_name = "URL",
+    conflicts_with_all = ["auth_token", "attach", "ephemeral_to", "to_user"],
+    help = "Post to an incoming webhook instead of using a token."
+  )]
+  pub webhook_url: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Commands {
+  /// Delete a message that was already sent.
+  Delete(DeleteArgs),
+  /// Read the channel's messages, newest first.
+  History(ReadArgs),
+  /// Pin or unpin a message, or list the channel's pins.
+  Pin(PinArgs),
+  /// Add, remove or list emoji reactions on a message.
+  React(ReactArgs),
+  /// Read a thread, its parent message first.
+  Replies(RepliesArgs),
+  /// List or cancel scheduled messages.
+  Schedule(ScheduleArgs),
+  /// Replace the body of a message that was already sent.
+  Update(Box<UpdateArgs>),
+}
+
+#[derive(Args, Debug, Default)]
+pub struct DeleteArgs {
+  #[arg(
+    short,
+    long,
+    help = "Timestamp of the message to delete. ex) '1734376519.228539'"
+  )]
+  pub timestamp: String,
+}
+
+#[derive(Args, Debug, Default)]
+pub struct PinArgs {
+  #[arg(
+    short,
+    long,
+    help = "Timestamp of the message to pin. ex) '1734376519.228539'. Leave out to list the pins."
+  )]
+  pub timestamp: Option<String>,
+  #[arg(
+    long,
+    requires = "timestamp",
+    help = "Only warn when the message is already pinned, or already unpinned with --remove."
+  )]
+  pub ignore_existing: bool,
+  #[arg(long, requires = "timestamp", help = "Unpin the message instead.")]
+  pub remove: bool,
+}
+
+#[derive(Args, Debug, Default)]
+pub struct ReactArgs {
+  #[arg(
+    short,
+    long,
+    help = "Timestamp of the message to react to. ex) '1734376519.228539'"
+  )]
+  pub timestamp: String,
+  #[arg(
+    long,
+    requires = "emoji",
+    help = "Only warn when the reaction is already there, or already gone with --remove."
+  )]
+  pub ignore_existing: bool,
+  #[arg(long, requires = "emoji", help = "Take the reaction back instead.")]
+  pub remove: bool,
+  #[arg(
+    value_parser = parse_emoji,
+    help = "Emoji with or without colons, ex) 'white_check_mark'. Leave out to list the reactions."
+  )]
+  pub emoji: Option<String>,
+}
+
+/// Which messages `history` and `replies` read.
+#[derive(Args, Debug, Default)]
+pub struct ReadArgs {
+  #[arg(
+    long,
+    value_name = "TS",
+    help = "Only messages at or after this timestamp. ex) '1734376519.228539'"
+  )]
+  pub oldest: Option<String>,
+  #[arg(
+    long,
+    value_name = "TS",
+    help = "Only messages at or before this timestamp."
+  )]
+  pub latest: Option<String>,
+  #[arg(
+    long,
+    value_name = "N",
+    value_parser = RangedU64ValueParser::<usize>::new().range(1..),
+    help = "Read at most this many messages. [default: all]"
+  )]
+  pub limit: Option<usize>,
+}
+
+#[derive(Args, Debug, Default)]
+pub struct RepliesArgs {
+  #[arg(
+    short,
+    long,
+    help = "Timestamp of the thread's parent message. ex) '1734376519.228539'"
+  )]
+  pub timestamp: String,
+  #[command(flatten)]
+  pub read: ReadArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct ScheduleArgs {
+  #[command(subcommand)]
+  pub command: ScheduleCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ScheduleCommands {
+  /// Cancel a scheduled message.
+  Delete(DeleteScheduledArgs),
+  /// List scheduled messages, only those in the channel if one is set.
+  List,
+}
+
+#[derive(Args, Debug, Default)]
+pub struct DeleteScheduledArgs {
+  #[arg(help = "Id of the scheduled message. ex) 'Q1298393284'")]
+  pub id: String,
+}
+
+#[derive(Args, Debug, Default)]
+pub struct UpdateArgs {
+  #[arg(
+    short,
+    long,
+    help = "Timestamp of the message to update. ex) '1734376519.228539'"
+  )]
+  pub timestamp: String,
+  #[command(flatten)]
+  pub body: MessageArgs,
+}
+
+/// Everything that makes up a message body, shared by sending and updating.
+#[derive(Args, Debug, Default)]
+pub struct MessageArgs {
+  #[arg(
+    short,
+    long,
+    help = "Block Kit blocks as inline json, ex) '[{\"type\":\"divider\"}]'"
+  )]
+  pub blocks: Option<String>,
+  #[arg(
+    long,
+    conflicts_with = "blocks",
+    help = "Read Block Kit blocks json from a file."
+  )]
+  pub blocks_file: Option<PathBuf>,
+  #[arg(
+    long,
+    value_parser = parse_color,
+    help = "Attachment side bar color: good, warning, danger or hex like '#36a64f'."
+  )]
+  pub color: Option<String>,
+  #[arg(
+    long = "field",
+    value_parser = parse_field,
+    help = "Attachment field as 'Title=value', can be repeated."
+  )]
+  pub fields: Vec<Field>,
+  #[arg(
+    short,
+    long,
+    conflicts_with = "message",
+    help = "Read the message from a file. Omit MESSAGE or pass '-' to read it from stdin."
+  )]
+  pub file: Option<PathBuf>,
+  #[arg(long, help = "Attachment footer.")]
+  pub footer: Option<String>,
+  #[arg(
+    help = "The message, put '--' before one that is a command's name, ex) -- pin"
+  )]
+  pub message: Option<String>,
+  #[arg(long, help = "Attachment text shown above the attachment.")]
+  pub pretext: Option<String>,
+  #[arg(long, help = "Attachment title.")]
+  pub title: Option<String>,
+  #[arg(long, requires = "title", help = "Url the attachment title links to.")]
+  pub title_link: Option<String>,
 }
 
 impl Cli {
-  pub fn get_channel(&self) -> Result<String, VarError> {
-    find_arg_or_env(self.channel.as_ref(), ENV_SLACK_CHANNEL)
+  /// The `--channel`s, or the comma separated channels of the env var.
+  pub fn get_channels(&self) -> Result<Vec<String>, VarError> {
+    if !self.channel.is_empty() {
+      return Ok(self.channel.clone());
+    }
+    let channels = split_channels(&find_arg_or_env(None, ENV_SLACK_CHANNEL)?);
+    if channels.is_empty() {
+      return Err(VarError::NotPresent);
+    }
+    Ok(channels)
   }
 
   pub fn get_oauth_token(&self) -> Result<String, VarError> {
     find_arg_or_env(self.auth_token.as_ref(), ENV_SLACK_TOKEN)
   }
+
+  /// search precedence: --api-base, env var, none for slack's own api
+  pub fn find_api_base(&self) -> Option<String> {
+    self.api_base.clone().or_else(|| env::var(ENV_SLACK_API_BASE).ok())
+  }
+
+  /// The retry policy, library defaults for anything not set.
+  pub fn get_retry(&self) -> Retry {
+    let default = Retry::default();
+    Retry {
+      max_retries: self.max_retries.unwrap_or(default.max_retries),
+      timeout: self.timeout.unwrap_or(default.timeout),
+      ..default
+    }
+  }
+
+  /// search precedence: --webhook-url, then the env var when no token is set
+  pub fn find_webhook_url(&self) -> Option<String> {
+    if self.webhook_url.is_some() {
+      return self.webhook_url.clone();
+    }
+    if self.auth_token.is_some() || env::var(ENV_SLACK_TOKEN).is_ok() {
+      return None;
+    }
+    env::var(ENV_SLACK_WEBHOOK).ok()
+  }
+
+  /// Like `get_channels` for commands where the channel is optional.
+  pub fn find_channels(&self) -> Vec<String> {
+    if !self.channel.is_empty() {
+      return self.channel.clone();
+    }
+    env::var(ENV_SLACK_CHANNEL).map(|c| split_channels(&c)).unwrap_or_default()
+  }
+
+  /// Unix time to schedule the message for, none to send it now.
+  pub fn get_post_at(&self) -> Option<i64> {
+    let delay = |d: Duration| {
+      time::now().saturating_add(i64::try_from(d.as_secs()).unwrap_or(i64::MAX))
+    };
+    self.at.or_else(|| self.delay.map(delay))
+  }
+}
+
+impl ReadArgs {
+  pub fn to_range(&self) -> Range {
+    Range {
+      oldest: self.oldest.clone(),
+      latest: self.latest.clone(),
+      limit: self.limit,
+    }
+  }
+}
+
+impl MessageArgs {
+  /// The text, blocks and attachment of a message to `channel`.
+  pub fn to_message(&self, channel: String) -> Result<Message, CliError> {
+    Ok(Message {
+      attachments: self.get_attachment().map(|a| vec![a]),
+      blocks: self.get_blocks()?,
+      ..Message::new(channel, self.get_message()?)
+    })
+  }
+
+  /// search precedence: --file, message arg, stdin
+  ///
+  /// With blocks or an attachment the text is only a fallback, so stdin is
+  /// read for it only when asked to with '-'.
+  pub fn get_message(&self) -> Result<String, CliError> {
+    if let Some(path) = &self.file {
+      return non_empty(&fs::read_to_string(path)?);
+    }
+    let has_blocks = self.blocks.is_some() || self.blocks_file.is_some();
+    match self.message.as_deref() {
+      None if has_blocks || self.has_attachment() => Ok(String::new()),
+      Some("-") | None => {
+        let stdin = io::stdin();
+        if stdin.is_terminal() {
+          return Err(CliError::Usage(String::from("no message provided")));
+        }
+        read_message(stdin)
+      }
+      Some(message) => non_empty(message),
+    }
+  }
+
+  /// search precedence: --blocks, --blocks-file
+  pub fn get_blocks(&self) -> Result<Option<Vec<Block>>, CliError> {
+    let json = match (&self.blocks, &self.blocks_file) {
+      (Some(json), _) => json.clone(),
+      (None, Some(path)) => fs::read_to_string(path)?,
+      (None, None) => return Ok(None),
+    };
+    blocks::parse_blocks(&json)
+      .map(Some)
+      .map_err(|e| CliError::Usage(format!("invalid blocks json: {e}")))
+  }
+
+  /// A single attachment built from the attachment flags, if any were set.
+  pub fn get_attachment(&self) -> Option<Attachment> {
+    if !self.has_attachment() {
+      return None;
+    }
+    Some(Attachment {
+      color: self.color.clone(),
+      fields: self.fields.clone(),
+      footer: self.footer.clone(),
+      pretext: self.pretext.clone(),
+      title: self.title.clone(),
+      title_link: self.title_link.clone(),
+      ..Attachment::default()
+    })
+  }
+
+  fn has_attachment(&self) -> bool {
+    self.color.is_some()
+      || !self.fields.is_empty()
+      || self.footer.is_some()
+      || self.pretext.is_some()
+      || self.title.is_some()
+  }
+}
+
+fn parse_color(s: &str) -> Result<String, String> {
+  if attachment::is_valid_color(s) {
+    Ok(s.to_owned())
+  } else {
+    Err(String::from("expected good, warning, danger or a hex color"))
+  }
+}
+
+/// Emoji work with or without colons, ex) 'tada' or ':tada:'.
+fn parse_emoji(s: &str) -> Result<String, String> {
+  match reactions::emoji_name(s) {
+    "" => Err(String::from("expected an emoji name, ex) 'tada'")),
+    name => Ok(name.to_owned()),
+  }
+}
+
+/// `icon_emoji` needs the colons that reactions go without.
+fn parse_icon(s: &str) -> Result<String, String> {
+  Ok(format!(":{}:", parse_emoji(s)?))
+}
+
+fn parse_field(s: &str) -> Result<Field, String> {
+  match s.split_once('=') {
+    Some((title, value)) if !title.is_empty() => Ok(Field::new(title, value)),
+    _ => Err(String::from("expected 'Title=value'")),
+  }
+}
+
+pub fn read_message(mut reader: impl Read) -> Result<String, CliError> {
+  let mut message = String::new();
+  reader.read_to_string(&mut message)?;
+  non_empty(&message)
+}
+
+/// Piped input usually ends with a newline slack doesn't need.
+fn non_empty(message: &str) -> Result<String, CliError> {
+  let trimmed = message.trim_end_matches(['\r', '\n']);
+  if trimmed.trim().is_empty() {
+    return Err(CliError::Usage(String::from("message is empty")));
+  }
+  Ok(trimmed.to_owned())
+}
+
+fn split_channels(channels: &str) -> Vec<String> {
+  channels
+    .split(',')
+    .map(str::trim)
+    .filter(|c| !c.is_empty())
+    .map(String::from)
+    .collect()
+}
+
+/// Failures of a cli run, each category mapped to its own exit code.
+#[derive(Debug)]
+pub enum CliError {
+  Env(VarError),
+  Input(io::Error),
+  /// Sending to several channels failed for `failed` of them, `code` is
+  /// `EXIT_PARTIAL` unless every channel failed.
+  Partial {
+    failed: usize,
+    total: usize,
+    code: u8,
+  },
+  Slack(SlackError),
+  Usage(String),
+}
+
+impl CliError {
+  pub fn code(&self) -> u8 {
+    match self {
+      CliError::Env(_) => EXIT_CONFIG,
+      CliError::Input(_) => EXIT_INPUT,
+      CliError::Partial { code, .. } => *code,
+      CliError::Usage(_) => EXIT_USAGE,
+      CliError::Slack(e) => match e {
+        SlackError::Transport(_) => EXIT_TRANSPORT,
+        SlackError::Status { status, .. }
+          if *status == StatusCode::TOO_MANY_REQUESTS =>
+        {
+          EXIT_RATE_LIMITED
+        }
+        SlackError::Status { .. } => EXIT_STATUS,
+        SlackError::Decode(_) => EXIT_DECODE,
+        SlackError::Invalid(_) => EXIT_USAGE,
+        SlackError::Api { error, .. } if error.is_auth() => EXIT_AUTH,
+        SlackError::Api { error, .. } if error.is_channel() => EXIT_CHANNEL,
+        SlackError::Api { error, .. } if error.is_message() => EXIT_MESSAGE,
+        SlackError::Api { error, .. } if error.is_user() => EXIT_USER,
+        SlackError::Api { error: ApiError::RateLimited, .. } => {
+          EXIT_RATE_LIMITED
+        }
+        SlackError::Api { .. } => EXIT_API,
+        SlackError::NotFound { kind: "user", .. }
+        | SlackError::Ambiguous { kind: "user", .. } => EXIT_USER,
+        SlackError::NotFound { .. } | SlackError::Ambiguous { .. } => {
+          EXIT_CHANNEL
+        }
+      },
+    }
+  }
+
+  pub fn exit_code(&self) -> ExitCode {
+    ExitCode::from(self.code())
+  }
+}
+
+impl Display for CliError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    match self {
+      CliError::Env(e) => write!(f, "missing configuration: {e}"),
+      CliError::Input(e) => write!(f, "could not read message: {e}"),
+      CliError::Partial { failed, total, .. } => {
+        write!(f, "{failed} of {total} channels failed")
+      }
+      CliError::Slack(e) => write!(f, "{e}"),
+      CliError::Usage(e) => f.write_str(e),
+    }
+  }
+}
+
+impl std::error::Error for CliError {}
+
+impl From<VarError> for CliError {
+  fn from(e: VarError) -> Self {
+    CliError::Env(e)
+  }
+}
+
+impl From<io::Error> for CliError {
+  fn from(e: io::Error) -> Self {
+    CliError::Input(e)
+  }
+}
+
+impl From<SlackError> for CliError {
+  fn from(e: SlackError) -> Self {
+    CliError::Slack(e)
+  }
 }
 
 pub fn print_completions<G: Generator>(gen: G, cmd: &mut Command) {
@@ -50,7 +625,7 @@ fn find_arg_or_env(
   arg: Option<&String>, env_var: &str,
 ) -> Result<String, VarError> {
   if let Some(val) = arg {
-    return Ok(val.to_string());
+    return Ok(val.clone());
   }
 
   match env::var(env_var) {
@@ -66,7 +641,7 @@ fn find_arg_or_env(
 mod tests {
   use super::*;
   use clap::builder::{Str, StyledStr};
-  use clap::{Command, CommandFactory, Id};
+  use clap::{Arg, CommandFactory, Id};
   use serial_test::serial;
   use std::error::Error;
 
@@ -83,7 +658,13 @@ mod tests {
     assert!(cli.get_display_name().is_none());
     assert!(cli.get_long_version().is_none());
     assert!(cli.get_long_version().is_none());
-    assert!(cli.get_subcommands().collect::<Vec<&Command>>().is_empty());
+    let subcommands = cli.get_subcommands().map(Command::get_name);
+    assert_eq!(
+      vec![
+        "delete", "history", "pin", "react", "replies", "schedule", "update"
+      ],
+      subcommands.collect::<Vec<&str>>()
+    );
     assert_eq!(Some("Christian Lansford"), cli.get_author());
     assert_eq!("slack-message", cli.get_name());
     Ok(())
@@ -113,18 +694,46 @@ mod tests {
   fn command_groups() -> Result<(), Box<dyn Error>> {
     let cli = Cli::command();
     cli.clone().debug_assert();
-    let mut contains_cli = false;
+    // clap leaves the Cli group empty once it has a subcommand or flatten
+    let cli_args = cli.get_arguments().map(Arg::get_id).collect::<Vec<&Id>>();
+    let expected_args = vec![
+      Id::from("api_base"),
+      Id::from("at"),
+      Id::from("attach"),
+      Id::from("auth_token"),
+      Id::from("channel"),
+      Id::from("completion"),
+      Id::from("delay"),
+      Id::from("ephemeral_to"),
+      Id::from("icon"),
+      Id::from("max_retries"),
+      Id::from("output"),
+      Id::from("permalink"),
+      Id::from("timeout"),
+      Id::from("to_user"),
+      Id::from("username"),
+      Id::from("webhook_url"),
+    ];
+    for expected_arg in expected_args {
+      println!("arg: {expected_arg}");
+      assert!(cli_args.contains(&&expected_arg));
+    }
+    let mut contains_message_args = false;
     for arg_group in cli.get_groups() {
-      if "Cli" == arg_group.get_id().as_str() {
-        contains_cli = true;
+      if "MessageArgs" == arg_group.get_id().as_str() {
+        contains_message_args = true;
         let args = arg_group.get_args().collect::<Vec<&Id>>();
         let expected_args = vec![
-          Id::from("auth_token"),
-          Id::from("channel"),
-          Id::from("completion"),
-          Id::from("icon"),
+          Id::from("blocks"),
+          Id::from("blocks_file"),
+          Id::from("color"),
+          Id::from("fields"),
+          Id::from("file"),
+          Id::from("footer"),
           Id::from("message"),
-          Id::from("username"),
+          Id::from("pretext"),
+          Id::from("title"),
+          Id::from("title_link"),
         ];
         for expected_arg in expected_args {
           println!("arg: {expected_arg}");
@@ -132,7 +741,7 @@ mod tests {
         }
       }
     }
-    assert!(contains_cli);
+    assert!(contains_message_args);
     Ok(())
   }
 
@@ -157,6 +766,10 @@ mod tests {
   #[test]
   fn icon_flag() -> Result<(), Box<dyn Error>> {
     flag_test("icon", Some('i'), Some("icon"), "ICON")?;
+    for icon in ["rocket", ":rocket:"] {
+      let cli = Cli::try_parse_from(["slack-message", "hi", "--icon", icon])?;
+      assert_eq!(Some(":rocket:"), cli.icon.as_deref());
+    }
     Ok(())
   }
 
@@ -167,9 +780,14 @@ mod tests {
   }
 
   #[test]
-  fn message_arg() -> Result<(), Box<dyn Error>> {
-    flag_test("message", None, None, "MESSAGE")?;
-    Ok(())
+  fn message_arg() {
+    let cli = Cli::command();
+    let arg = cli.get_arguments().find(|arg| arg.get_id() == "message");
+    let arg = arg.expect("message arg");
+    assert!(arg.is_positional());
+    assert!(arg.get_value_names().unwrap().contains(&Str::from("MESSAGE")));
+    let help = arg.get_help().map(ToString::to_string).unwrap_or_default();
+    assert!(help.contains("'--'"), "{help}");
   }
 
   #[test]
@@ -211,7 +829,7 @@ mod tests {
         );
         unreachable!()
       }
-      Err(e) => handle_env_var_error(e)?,
+      Err(e) => handle_env_var_error(&e)?,
     };
     let expected = "environment variable not found";
     assert_eq!(
@@ -222,14 +840,52 @@ mod tests {
   }
 
   #[test]
-  fn get_channel_arg() -> Result<(), Box<dyn Error>> {
-    let expected = String::from("testArgChannel");
-    let cli = Cli { channel: Some(expected.clone()), ..Default::default() };
-    let actual = cli.get_channel()?;
+  #[serial]
+  fn find_api_base() {
+    env::set_var(ENV_SLACK_API_BASE, "http://env/api");
+    let cli = Cli { ..Default::default() };
+    assert_eq!(Some("http://env/api"), cli.find_api_base().as_deref());
+    let cli = Cli {
+      api_base: Some(String::from("http://arg/api")),
+      ..Default::default()
+    };
+    assert_eq!(Some("http://arg/api"), cli.find_api_base().as_deref());
+    env::remove_var(ENV_SLACK_API_BASE);
+    let cli = Cli { ..Default::default() };
+    assert_eq!(None, cli.find_api_base());
+  }
+
+  #[test]
+  #[serial]
+  fn find_webhook_url() -> Result<(), Box<dyn Error>> {
+    env::remove_var(ENV_SLACK_TOKEN);
+    env::set_var(ENV_SLACK_WEBHOOK, "https://hooks.slack.com/env");
+    let cli = Cli { ..Default::default() };
     assert_eq!(
-      expected, actual,
-      "\n  expected: {expected}\n  actual: {actual}"
+      Some("https://hooks.slack.com/env"),
+      cli.find_webhook_url().as_deref()
     );
+    env::set_var(ENV_SLACK_TOKEN, "xoxb-test");
+    assert_eq!(None, cli.find_webhook_url());
+    let cli = Cli {
+      webhook_url: Some(String::from("https://hooks.slack.com/arg")),
+      ..Default::default()
+    };
+    assert_eq!(
+      Some("https://hooks.slack.com/arg"),
+      cli.find_webhook_url().as_deref()
+    );
+    env::remove_var(ENV_SLACK_TOKEN);
+    env::remove_var(ENV_SLACK_WEBHOOK);
+    Ok(())
+  }
+
+  #[test]
+  fn get_channel_arg() -> Result<(), Box<dyn Error>> {
+    let expected = vec![String::from("testArgChannel")];
+    let cli = Cli { channel: expected.clone(), ..Default::default() };
+    let actual = cli.get_channels()?;
+    assert_eq!(expected, actual);
     Ok(())
   }
 
@@ -237,29 +893,53 @@ mod tests {
   #[serial]
   fn get_channel_env() -> Result<(), Box<dyn Error>> {
     let cli = Cli { ..Default::default() };
-    let expected = String::from("testEnvChannel");
-    env::set_var(ENV_SLACK_CHANNEL, expected.clone());
-    let actual = cli.get_channel()?;
-    assert_eq!(
-      expected, actual,
-      "\n  expected: {expected}\n  actual: {actual}"
-    );
+    let expected = vec![String::from("testEnvChannel")];
+    env::set_var(ENV_SLACK_CHANNEL, "testEnvChannel");
+    let actual = cli.get_channels()?;
+    assert_eq!(expected, actual);
     env::remove_var(ENV_SLACK_CHANNEL);
     Ok(())
   }
 
+  #[test]
+  #[serial]
+  fn get_channels_env_list() -> Result<(), Box<dyn Error>> {
+    let cli = Cli { ..Default::default() };
+    env::set_var(ENV_SLACK_CHANNEL, "#releases, deploys,,C0000000001");
+    let actual = cli.get_channels()?;
+    assert_eq!(vec!["#releases", "deploys", "C0000000001"], actual);
+    env::set_var(ENV_SLACK_CHANNEL, " , ");
+    assert_eq!(Err(VarError::NotPresent), cli.get_channels());
+    env::remove_var(ENV_SLACK_CHANNEL);
+    Ok(())
+  }
+
+  #[test]
+  fn channel_flag_repeated() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::try_parse_from([
+      "slack-message",
+      "v1.2.3 released",
+      "-c",
+      "releases",
+      "--channel",
+      "deploys,C0000000001",
+    ])?;
+    assert_eq!(vec!["releases", "deploys", "C0000000001"], cli.channel);
+    Ok(())
+  }
+
   #[test]
   #[serial]
   fn get_channel_fail() -> Result<(), Box<dyn Error>> {
     env::remove_var(ENV_SLACK_CHANNEL);
     let cli = Cli { ..Default::default() };
-    let actual = match cli.get_channel() {
+    let actual = match cli.get_channels() {
       Ok(_) => {
         unreachable!(
           "This shouldn't be reachable because the channel shouldn't be set."
         )
       }
-      Err(e) => handle_env_var_error(e)?,
+      Err(e) => handle_env_var_error(&e)?,
     };
     let expected = "environment variable not found";
     assert_eq!(
@@ -269,7 +949,530 @@ mod tests {
     Ok(())
   }
 
-  fn handle_env_var_error(e: VarError) -> Result<String, Box<dyn Error>> {
+  #[test]
+  fn get_message_arg() -> Result<(), Box<dyn Error>> {
+    let cli = MessageArgs {
+      message: Some(String::from("testMessage")),
+      ..Default::default()
+    };
+    assert_eq!("testMessage", cli.get_message()?);
+    Ok(())
+  }
+
+  #[test]
+  fn get_message_file() -> Result<(), Box<dyn Error>> {
+    let path = env::temp_dir().join("slack-message-get-message-file.txt");
+    fs::write(&path, "line one\nline two\n")?;
+    let cli = MessageArgs { file: Some(path.clone()), ..Default::default() };
+    let actual = cli.get_message();
+    fs::remove_file(path)?;
+    assert_eq!("line one\nline two", actual?);
+    Ok(())
+  }
+
+  #[test]
+  fn get_message_missing_file() {
+    let path = env::temp_dir().join("slack-message-does-not-exist.txt");
+    let cli = MessageArgs { file: Some(path), ..Default::default() };
+    let actual = cli.get_message().unwrap_err();
+    assert_eq!(EXIT_INPUT, actual.code());
+  }
+
+  #[test]
+  fn read_message_stdin() -> Result<(), Box<dyn Error>> {
+    let actual = read_message("build 1234 passed\r\n".as_bytes())?;
+    assert_eq!("build 1234 passed", actual);
+    Ok(())
+  }
+
+  #[test]
+  fn read_message_empty() {
+    let actual = read_message(" \n\n".as_bytes()).unwrap_err();
+    assert_eq!(EXIT_USAGE, actual.code());
+  }
+
+  #[test]
+  fn get_message_blocks_without_text() -> Result<(), Box<dyn Error>> {
+    let cli = MessageArgs {
+      blocks: Some(String::from(r#"[{"type":"divider"}]"#)),
+      ..Default::default()
+    };
+    assert_eq!("", cli.get_message()?);
+    Ok(())
+  }
+
+  #[test]
+  fn get_blocks_inline() -> Result<(), Box<dyn Error>> {
+    let cli = MessageArgs {
+      blocks: Some(String::from(r#"[{"type":"divider"}]"#)),
+      ..Default::default()
+    };
+    let actual = serde_json::to_string(&cli.get_blocks()?)?;
+    assert_eq!(r#"[{"type":"divider"}]"#, actual);
+    Ok(())
+  }
+
+  #[test]
+  fn get_blocks_file() -> Result<(), Box<dyn Error>> {
+    let path = env::temp_dir().join("slack-message-get-blocks-file.json");
+    fs::write(&path, r#"{"blocks":[{"type":"divider"}]}"#)?;
+    let cli =
+      MessageArgs { blocks_file: Some(path.clone()), ..Default::default() };
+    let actual = cli.get_blocks();
+    fs::remove_file(path)?;
+    let actual = serde_json::to_string(&actual?)?;
+    assert_eq!(r#"[{"type":"divider"}]"#, actual);
+    Ok(())
+  }
+
+  #[test]
+  fn get_blocks_invalid() {
+    let cli =
+      MessageArgs { blocks: Some(String::from("[{}]")), ..Default::default() };
+    assert_eq!(EXIT_USAGE, cli.get_blocks().unwrap_err().code());
+  }
+
+  #[test]
+  fn get_blocks_none() -> Result<(), Box<dyn Error>> {
+    assert!(MessageArgs::default().get_blocks()?.is_none());
+    Ok(())
+  }
+
+  #[test]
+  fn get_attachment_flags() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::try_parse_from([
+      "slack-message",
+      "build failed",
+      "--color",
+      "danger",
+      "--field",
+      "Build=1234",
+      "--field",
+      "Branch=main=stable",
+      "--title",
+      "CI",
+      "--title-link",
+      "https://ci.example.com",
+    ])?;
+    let expected = Attachment::new()
+      .color("danger")
+      .field(Field::new("Build", "1234"))
+      .field(Field::new("Branch", "main=stable"))
+      .title("CI")
+      .title_link("https://ci.example.com");
+    assert_eq!(Some(expected), cli.body.get_attachment());
+    Ok(())
+  }
+
+  #[test]
+  fn get_attachment_none() {
+    let cli =
+      MessageArgs { message: Some(String::from("hi")), ..Default::default() };
+    assert!(cli.get_attachment().is_none());
+  }
+
+  #[test]
+  fn color_flag_invalid() {
+    let actual = Cli::try_parse_from(["slack-message", "hi", "--color", "red"]);
+    assert!(actual.is_err());
+  }
+
+  #[test]
+  fn field_flag_invalid() {
+    let actual = Cli::try_parse_from(["slack-message", "hi", "--field", "x"]);
+    assert!(actual.is_err());
+  }
+
+  #[test]
+  fn to_message() -> Result<(), Box<dyn Error>> {
+    let args = MessageArgs {
+      message: Some(String::from("deployed")),
+      color: Some(String::from("good")),
+      ..Default::default()
+    };
+    let expected = Message::new("C1", "deployed")
+      .attachments(vec![Attachment::new().color("good")]);
+    assert_eq!(expected, args.to_message(String::from("C1"))?);
+    Ok(())
+  }
+
+  #[test]
+  fn update_command() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::try_parse_from([
+      "slack-message",
+      "update",
+      "--channel",
+      "C1",
+      "--timestamp",
+      "1734376519.228539",
+      "deploy finished",
+    ])?;
+    assert_eq!(vec!["C1"], cli.channel);
+    let Some(Commands::Update(update)) = cli.command else {
+      panic!("expected update command, got: {:?}", cli.command);
+    };
+    assert_eq!("1734376519.228539", update.timestamp);
+    assert_eq!(Some("deploy finished"), update.body.message.as_deref());
+    Ok(())
+  }
+
+  #[test]
+  fn delete_command() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::try_parse_from([
+      "slack-message",
+      "delete",
+      "-t",
+      "1734376519.228539",
+    ])?;
+    let Some(Commands::Delete(delete)) = cli.command else {
+      panic!("expected delete command, got: {:?}", cli.command);
+    };
+    assert_eq!("1734376519.228539", delete.timestamp);
+    Ok(())
+  }
+
+  #[test]
+  fn history_command() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::try_parse_from([
+      "slack-message",
+      "history",
+      "--oldest",
+      "1734376519.228539",
+      "--limit",
+      "50",
+    ])?;
+    let Some(Commands::History(read)) = cli.command else {
+      panic!("expected history command, got: {:?}", cli.command);
+    };
+    let expected = Range {
+      oldest: Some(String::from("1734376519.228539")),
+      latest: None,
+      limit: Some(50),
+    };
+    assert_eq!(expected, read.to_range());
+    let actual =
+      Cli::try_parse_from(["slack-message", "history", "--limit", "0"]);
+    assert!(actual.is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn replies_command() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::try_parse_from([
+      "slack-message",
+      "replies",
+      "-t",
+      "1734376519.228539",
+      "--output",
+      "json",
+    ])?;
+    let Some(Commands::Replies(replies)) = cli.command else {
+      panic!("expected replies command, got: {:?}", cli.command);
+    };
+    assert_eq!("1734376519.228539", replies.timestamp);
+    assert_eq!(Range::default(), replies.read.to_range());
+    assert_eq!(Output::Json, cli.output);
+    Ok(())
+  }
+
+  #[test]
+  fn pin_command() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::try_parse_from([
+      "slack-message",
+      "pin",
+      "-t",
+      "1734376519.228539",
+      "--remove",
+    ])?;
+    let Some(Commands::Pin(pin)) = cli.command else {
+      panic!("expected pin command, got: {:?}", cli.command);
+    };
+    assert_eq!(Some("1734376519.228539"), pin.timestamp.as_deref());
+    assert!(pin.remove);
+    assert!(!pin.ignore_existing);
+    for flag in ["--remove", "--ignore-existing"] {
+      assert!(Cli::try_parse_from(["slack-message", "pin", flag]).is_err());
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn command_name_as_message() -> Result<(), Box<dyn Error>> {
+    let names =
+      ["delete", "history", "pin", "react", "replies", "schedule", "update"];
+    for name in names {
+      // the bare name runs, or asks for the arguments of, the command
+      let bare = Cli::try_parse_from(["slack-message", name]);
+      assert!(!matches!(bare, Ok(Cli { command: None, .. })), "{name}");
+      let cli = Cli::try_parse_from(["slack-message", "--", name])?;
+      assert!(cli.command.is_none(), "{name}");
+      assert_eq!(Some(name), cli.body.message.as_deref());
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn react_command() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::try_parse_from([
+      "slack-message",
+      "react",
+      "-t",
+      "1734376519.228539",
+      ":white_check_mark:",
+    ])?;
+    let Some(Commands::React(react)) = cli.command else {
+      panic!("expected react command, got: {:?}", cli.command);
+    };
+    assert_eq!("1734376519.228539", react.timestamp);
+    assert_eq!(Some("white_check_mark"), react.emoji.as_deref());
+    assert!(!react.remove);
+    assert!(!react.ignore_existing);
+    for flag in ["--remove", "--ignore-existing"] {
+      let actual =
+        Cli::try_parse_from(["slack-message", "react", "-t", "1.2", flag]);
+      assert!(actual.is_err(), "{flag}");
+    }
+    let actual =
+      Cli::try_parse_from(["slack-message", "react", "-t", "1.2", "::"]);
+    assert!(actual.is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn get_post_at_at() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::try_parse_from([
+      "slack-message",
+      "freeze starts in 1 hour",
+      "--at",
+      "2025-01-31T17:00:00Z",
+    ])?;
+    assert_eq!(Some(1_738_342_800), cli.get_post_at());
+    Ok(())
+  }
+
+  #[test]
+  fn get_post_at_in() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::try_parse_from(["slack-message", "reminder", "--in", "2h"])?;
+    let before = time::now();
+    let actual = cli.get_post_at().unwrap();
+    assert!((before + 7_200..=time::now() + 7_200).contains(&actual));
+    Ok(())
+  }
+
+  #[test]
+  fn get_post_at_none() {
+    assert!(Cli::default().get_post_at().is_none());
+  }
+
+  #[test]
+  fn at_conflicts_with_in() {
+    let actual = Cli::try_parse_from([
+      "slack-message",
+      "hi",
+      "--at",
+      "1738342800",
+      "--in",
+      "2h",
+    ]);
+    assert!(actual.is_err());
+  }
+
+  #[test]
+  fn attach_flag() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::try_parse_from([
+      "slack-message",
+      "nightly build logs",
+      "--attach",
+      "build.log",
+      "--attach",
+      "coverage.html",
+    ])?;
+    let expected =
+      vec![PathBuf::from("build.log"), PathBuf::from("coverage.html")];
+    assert_eq!(expected, cli.attach);
+    for conflict in [["--in", "1h"], ["--color", "danger"]] {
+      let actual = Cli::try_parse_from(
+        ["slack-message", "hi", "--attach", "build.log"]
+          .into_iter()
+          .chain(conflict),
+      );
+      assert!(actual.is_err(), "{conflict:?}");
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn retry_flags() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::try_parse_from(["slack-message", "hi"])?;
+    assert_eq!(Retry::default(), cli.get_retry());
+    let cli = Cli::try_parse_from([
+      "slack-message",
+      "delete",
+      "-t",
+      "1.2",
+      "--max-retries",
+      "0",
+      "--timeout",
+      "10s",
+    ])?;
+    let actual = cli.get_retry();
+    assert_eq!(0, actual.max_retries);
+    assert_eq!(Duration::from_secs(10), actual.timeout);
+    Ok(())
+  }
+
+  #[test]
+  fn output_flag() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::try_parse_from(["slack-message", "hi"])?;
+    assert_eq!(Output::Text, cli.output);
+    let cli = Cli::try_parse_from([
+      "slack-message",
+      "delete",
+      "-t",
+      "1.2",
+      "--output",
+      "ts-only",
+    ])?;
+    assert_eq!(Output::TsOnly, cli.output);
+    assert!(Cli::try_parse_from(["slack-message", "-o", "yaml", "hi"]).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn permalink_flag() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::try_parse_from(["slack-message", "hi", "--permalink"])?;
+    assert!(cli.permalink);
+    let actual =
+      Cli::try_parse_from(["slack-message", "hi", "--permalink", "--in", "1h"]);
+    assert!(actual.is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn to_user_flag() -> Result<(), Box<dyn Error>> {
+    let cli =
+      Cli::try_parse_from(["slack-message", "hi", "--to-user", "@alice"])?;
+    assert_eq!(vec!["@alice"], cli.to_user);
+    let cli = Cli::try_parse_from([
+      "slack-message",
+      "hi",
+      "--to-user",
+      "@alice",
+      "--to-user",
+      "bob@example.com",
+    ])?;
+    assert_eq!(vec!["@alice", "bob@example.com"], cli.to_user);
+    let cli = Cli::try_parse_from([
+      "slack-message",
+      "delete",
+      "-t",
+      "1734376519.228539",
+      "--to-user",
+      "alice@example.com",
+    ])?;
+    assert_eq!(vec!["alice@example.com"], cli.to_user);
+    let actual = Cli::try_parse_from([
+      "slack-message",
+      "hi",
+      "--to-user",
+      "@alice",
+      "-c",
+      "deploys",
+    ]);
+    assert!(actual.is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn ephemeral_to_flag() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::try_parse_from([
+      "slack-message",
+      "your PR build failed",
+      "--ephemeral-to",
+      "U1",
+    ])?;
+    assert_eq!(Some("U1"), cli.ephemeral_to.as_deref());
+    let actual = Cli::try_parse_from([
+      "slack-message",
+      "hi",
+      "--ephemeral-to",
+      "U1",
+      "--in",
+      "1h",
+    ]);
+    assert!(actual.is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn schedule_commands() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::try_parse_from(["slack-message", "schedule", "list"])?;
+    let Some(Commands::Schedule(schedule)) = cli.command else {
+      panic!("expected schedule command, got: {:?}", cli.command);
+    };
+    assert!(matches!(schedule.command, ScheduleCommands::List));
+    let cli =
+      Cli::try_parse_from(["slack-message", "schedule", "delete", "Q1"])?;
+    let Some(Commands::Schedule(schedule)) = cli.command else {
+      panic!("expected schedule command, got: {:?}", cli.command);
+    };
+    let ScheduleCommands::Delete(delete) = schedule.command else {
+      panic!("expected schedule delete, got: {:?}", schedule.command);
+    };
+    assert_eq!("Q1", delete.id);
+    Ok(())
+  }
+
+  #[test]
+  fn update_command_requires_timestamp() {
+    let actual = Cli::try_parse_from(["slack-message", "update", "text"]);
+    assert!(actual.is_err());
+  }
+
+  #[test]
+  fn file_flag_conflicts_with_message() {
+    let actual = Cli::try_parse_from(["slack-message", "-f", "a.txt", "hi"]);
+    assert!(actual.is_err());
+  }
+
+  #[test]
+  fn cli_error_codes() {
+    let api = |code: &str| CliError::Slack(ApiError::from_code(code).into());
+    assert_eq!(EXIT_CONFIG, CliError::Env(VarError::NotPresent).code());
+    assert_eq!(EXIT_AUTH, api("invalid_auth").code());
+    assert_eq!(EXIT_AUTH, api("not_authed").code());
+    assert_eq!(EXIT_CHANNEL, api("channel_not_found").code());
+    assert_eq!(EXIT_CHANNEL, api("not_in_channel").code());
+    assert_eq!(EXIT_RATE_LIMITED, api("ratelimited").code());
+    assert_eq!(EXIT_MESSAGE, api("message_not_found").code());
+    assert_eq!(EXIT_MESSAGE, api("cant_delete_message").code());
+    assert_eq!(EXIT_API, api("msg_too_long").code());
+    let status = |status| {
+      CliError::Slack(SlackError::Status { status, body: String::new() })
+    };
+    assert_eq!(EXIT_STATUS, status(StatusCode::BAD_GATEWAY).code());
+    assert_eq!(EXIT_RATE_LIMITED, status(StatusCode::TOO_MANY_REQUESTS).code());
+    let not_found = SlackError::NotFound {
+      kind: "channel",
+      name: String::from("deploys"),
+      close_matches: Vec::new(),
+    };
+    assert_eq!(EXIT_CHANNEL, CliError::Slack(not_found).code());
+    let ambiguous = SlackError::Ambiguous {
+      kind: "user",
+      name: String::from("sam"),
+      matches: Vec::new(),
+    };
+    assert_eq!(EXIT_USER, CliError::Slack(ambiguous).code());
+    assert_eq!(EXIT_USER, api("users_not_found").code());
+    let decode = serde_json::from_str::<u8>("").unwrap_err();
+    assert_eq!(EXIT_DECODE, CliError::Slack(decode.into()).code());
+    let partial = CliError::Partial { failed: 1, total: 3, code: EXIT_PARTIAL };
+    assert_eq!(EXIT_PARTIAL, partial.code());
+    assert_eq!("1 of 3 channels failed", partial.to_string());
+  }
+
+  fn handle_env_var_error(e: &VarError) -> Result<String, Box<dyn Error>> {
     match e {
       env::VarError::NotPresent => Ok(e.to_string()),
       env::VarError::NotUnicode(_) => {
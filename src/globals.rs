@@ -1,5 +1,35 @@
 //put slack api url strings here b/c we don't own them.
 
+/// Env var holding the slack app's OAuth token.
 pub const ENV_SLACK_TOKEN: &str = "SLACK_MESSAGE_TOKEN";
+/// Env var holding the id of the channel to send messages to.
 pub const ENV_SLACK_CHANNEL: &str = "SLACK_MESSAGE_CHANNEL";
-pub const POST_MES_URL: &str = "https://slack.com/api/chat.postMessage";
+/// Env var holding an incoming webhook url, used when there is no token.
+pub const ENV_SLACK_WEBHOOK: &str = "SLACK_MESSAGE_WEBHOOK";
+/// Env var holding the base url of a slack compatible api to use instead.
+pub const ENV_SLACK_API_BASE: &str = "SLACK_MESSAGE_API_BASE";
+pub const SLACK_API_URL: &str = "https://slack.com/api";
+
+// web api methods, appended to SLACK_API_URL
+pub const CHAT_DELETE: &str = "chat.delete";
+pub const CHAT_DELETE_SCHEDULED_MESSAGE: &str = "chat.deleteScheduledMessage";
+pub const CHAT_GET_PERMALINK: &str = "chat.getPermalink";
+pub const CHAT_POST_EPHEMERAL: &str = "chat.postEphemeral";
+pub const CHAT_POST_MESSAGE: &str = "chat.postMessage";
+pub const CHAT_SCHEDULE_MESSAGE: &str = "chat.scheduleMessage";
+pub const CHAT_SCHEDULED_MESSAGES_LIST: &str = "chat.scheduledMessages.list";
+pub const CHAT_UPDATE: &str = "chat.update";
+pub const CONVERSATIONS_HISTORY: &str = "conversations.history";
+pub const CONVERSATIONS_LIST: &str = "conversations.list";
+pub const CONVERSATIONS_OPEN: &str = "conversations.open";
+pub const CONVERSATIONS_REPLIES: &str = "conversations.replies";
+pub const FILES_COMPLETE_UPLOAD_EXTERNAL: &str = "files.completeUploadExternal";
+pub const FILES_GET_UPLOAD_URL_EXTERNAL: &str = "files.getUploadURLExternal";
+pub const PINS_ADD: &str = "pins.add";
+pub const PINS_LIST: &str = "pins.list";
+pub const PINS_REMOVE: &str = "pins.remove";
+pub const REACTIONS_ADD: &str = "reactions.add";
+pub const REACTIONS_GET: &str = "reactions.get";
+pub const REACTIONS_REMOVE: &str = "reactions.remove";
+pub const USERS_LIST: &str = "users.list";
+pub const USERS_LOOKUP_BY_EMAIL: &str = "users.lookupByEmail";
@@ -0,0 +1,16 @@
+//! Send slack messages from rust.
+//!
+//! ```no_run
+//! use slack_message::slack::{Client, Message};
+//!
+//! # async fn run() -> Result<(), slack_message::slack::SlackError> {
+//! let client = Client::new("xoxb-123...");
+//! let msg = Message::new("C12345ABCDE", "deploy started").username("deploy");
+//! let res = client.send_message(&msg).await?;
+//! println!("Message sent, timestamp: {}", res.ts);
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod globals;
+pub mod slack;
@@ -1,38 +1,496 @@
 mod cli;
-mod globals;
-mod slack;
+mod output;
+mod time;
 
-use crate::cli::Cli;
-use crate::slack::{Client, Message};
+use crate::cli::{
+  Cli, CliError, Commands, DeleteArgs, PinArgs, ReactArgs, ReadArgs,
+  ScheduleArgs, ScheduleCommands, UpdateArgs, EXIT_PARTIAL,
+};
+use crate::output::{Output, Report};
 use clap::{CommandFactory, Parser};
-use core::panic;
 use dotenvy::dotenv;
-use std::error::Error;
+use serde::Serialize;
+use slack_message::slack::chat::EphemeralResponse;
+use slack_message::slack::conversations::MAX_CONVERSATION_USERS;
+use slack_message::slack::files::{FileUpload, Share};
+use slack_message::slack::response::{OkResponse, Response};
+use slack_message::slack::webhook::Webhook;
+use slack_message::slack::{ApiError, Client, Message, SlackError};
+use std::process::ExitCode;
+use std::{fs, io};
+use tokio::task::JoinSet;
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn Error>> {
+async fn main() -> ExitCode {
   dotenv().ok();
   let args = Cli::parse();
+  match run(&args).await {
+    Ok(()) => ExitCode::SUCCESS,
+    Err(e) => {
+      eprintln!("Error: {e}");
+      e.exit_code()
+    }
+  }
+}
+
+async fn run(args: &Cli) -> Result<(), CliError> {
   if let Some(shell) = args.completion {
     cli::print_completions(shell, &mut Cli::command());
     return Ok(());
   };
 
-  let token = args.get_oauth_token()?;
+  if let Some(url) = args.find_webhook_url() {
+    return send_webhook(args, &Webhook::new(url)).await;
+  }
+  let mut slack = Client::new(&args.get_oauth_token()?).retry(args.get_retry());
+  if let Some(api_base) = args.find_api_base() {
+    slack = slack.api_url(api_base);
+  }
+  match &args.command {
+    Some(Commands::Delete(delete)) => {
+      delete_message(args, delete, &slack).await
+    }
+    Some(Commands::History(read)) => {
+      read_messages(args, read, None, &slack).await
+    }
+    Some(Commands::Pin(pin)) => pin_message(args, pin, &slack).await,
+    Some(Commands::React(react)) => react_to_message(args, react, &slack).await,
+    Some(Commands::Replies(replies)) => {
+      let thread = Some(replies.timestamp.as_str());
+      read_messages(args, &replies.read, thread, &slack).await
+    }
+    Some(Commands::Schedule(schedule)) => {
+      scheduled_messages(args, schedule, &slack).await
+    }
+    Some(Commands::Update(update)) => {
+      update_message(args, update, &slack).await
+    }
+    None => send_message(args, &slack).await,
+  }
+}
+
+/// Webhooks can only post, and only to their own channel unless they are
+/// legacy ones that take a `--channel` override.
+async fn send_webhook(args: &Cli, webhook: &Webhook) -> Result<(), CliError> {
+  let unsupported = args.command.is_some()
+    || !args.attach.is_empty()
+    || args.ephemeral_to.is_some()
+    || args.get_post_at().is_some()
+    || args.permalink
+    || !args.to_user.is_empty();
+  if unsupported {
+    return Err(CliError::Usage(String::from(
+      "a webhook can only send messages, set a token for anything else",
+    )));
+  }
+  if args.output == Output::TsOnly {
+    return Err(CliError::Usage(String::from(
+      "a webhook doesn't return the message's timestamp",
+    )));
+  }
+  let channel = match args.find_channels().as_slice() {
+    [] => String::new(),
+    [channel] => channel.clone(),
+    _ => {
+      return Err(CliError::Usage(String::from(
+        "a webhook can only send to one channel",
+      )))
+    }
+  };
   let msg = Message {
-    channel: &args.get_channel()?,
-    icon_emoji: args.icon.as_deref(),
-    text: &args.message,
-    thread_ts: args.timestamp.as_deref(),
-    username: args.username.as_deref(),
-  };
-  let slack = Client::new(&token);
-  let res = slack.send_message(&msg).await?;
-  if res.ok {
-    println!("Message sent, timestamp: {}", res.ts);
+    icon_emoji: args.icon.clone(),
+    thread_ts: args.timestamp.clone(),
+    username: args.username.clone(),
+    ..args.body.to_message(channel)?
+  };
+  webhook.send_message(&msg).await?;
+  let ok = serde_json::json!({ "ok": true });
+  println!("{}", Report::new("Message sent", "", &ok)?.render(args.output));
+  Ok(())
+}
+
+/// How a message goes out, the same for every channel it is sent to.
+#[derive(Debug, Clone)]
+enum Delivery {
+  Ephemeral(String),
+  Now,
+  Scheduled(i64),
+}
+
+async fn send_message(args: &Cli, slack: &Client) -> Result<(), CliError> {
+  if !args.attach.is_empty() {
+    return upload_files(args, slack).await;
+  }
+  let delivery = match (&args.ephemeral_to, args.get_post_at()) {
+    (Some(user), _) => Delivery::Ephemeral(user.clone()),
+    (None, Some(post_at)) => Delivery::Scheduled(post_at),
+    (None, None) => Delivery::Now,
+  };
+  let msg = Message {
+    icon_emoji: args.icon.clone(),
+    thread_ts: args.timestamp.clone(),
+    username: args.username.clone(),
+    ..args.body.to_message(String::new())?
+  };
+  if args.to_user.is_empty() {
+    let channels = args.get_channels()?;
+    if channels.len() > 1 {
+      return fan_out(args, slack, &channels, &msg, &delivery).await;
+    }
+  }
+  let msg = Message { channel: channel(args, slack).await?, ..msg };
+  let report = deliver(slack, &msg, &delivery, args.permalink).await?;
+  println!("{}", report.render(args.output));
+  Ok(())
+}
+
+/// Send `msg` to every channel concurrently and print how each went, in the
+/// order the channels were given.
+///
+/// Every name is resolved first so a typo doesn't leave the message in only
+/// some of the channels.
+async fn fan_out(
+  args: &Cli, slack: &Client, channels: &[String], msg: &Message,
+  delivery: &Delivery,
+) -> Result<(), CliError> {
+  let (output, permalink) = (args.output, args.permalink);
+  let ids = slack.resolve_channels(channels).await?;
+  let mut tasks = JoinSet::new();
+  for (i, id) in ids.iter().cloned().enumerate() {
+    let (slack, delivery) = (slack.clone(), delivery.clone());
+    let msg = Message { channel: id, ..msg.clone() };
+    tasks.spawn(async move {
+      (i, deliver(&slack, &msg, &delivery, permalink).await)
+    });
+  }
+  let mut results = tasks.join_all().await;
+  results.sort_unstable_by_key(|(i, _)| *i);
+
+  let mut failed = Vec::new();
+  for (i, result) in results {
+    let channel = &channels[i];
+    match result {
+      // every response has its channel, failures get a line of their own
+      Ok(sent) if output == Output::Json => println!("{}", sent.render(output)),
+      Ok(sent) if output == Output::TsOnly => {
+        println!("{channel}\t{}", sent.render(output));
+      }
+      Ok(sent) => println!("{channel}: {}", sent.render(output)),
+      Err(e) => {
+        eprintln!("{channel}: Error: {e}");
+        if output == Output::Json {
+          let failure = serde_json::json!({
+            "ok": false,
+            "channel": ids[i],
+            "error": e.to_string(),
+          });
+          println!("{failure}");
+        }
+        failed.push(CliError::from(e).code());
+      }
+    }
+  }
+  match failed.first() {
+    None => Ok(()),
+    Some(&code) => Err(CliError::Partial {
+      failed: failed.len(),
+      total: channels.len(),
+      code: if failed.len() < channels.len() { EXIT_PARTIAL } else { code },
+    }),
+  }
+}
+
+/// Slack's response to a posted message with its url, for `--permalink`.
+#[derive(Debug, Serialize)]
+struct Sent<'a> {
+  #[serde(flatten)]
+  res: &'a Response,
+  permalink: &'a str,
+}
+
+/// Slack's response to an ephemeral message, which leaves out the channel.
+#[derive(Debug, Serialize)]
+struct Ephemeral<'a> {
+  channel: &'a str,
+  #[serde(flatten)]
+  res: &'a EphemeralResponse,
+}
+
+/// Send `msg` as `delivery` says, returning what to report on success.
+///
+/// A scheduled message has no timestamp until it is posted, its id stands
+/// in for one. With `permalink` a posted message's url is looked up too,
+/// ts-only then prints it after the timestamp.
+async fn deliver(
+  slack: &Client, msg: &Message, delivery: &Delivery, permalink: bool,
+) -> Result<Report, SlackError> {
+  match delivery {
+    Delivery::Ephemeral(user) => {
+      let res = slack.post_ephemeral(msg, user).await?;
+      print_warning(res.warning.as_deref());
+      let text =
+        format!("Ephemeral message sent, timestamp: {}", res.message_ts);
+      let json = Ephemeral { channel: &msg.channel, res: &res };
+      Report::new(text, &res.message_ts, &json)
+    }
+    Delivery::Scheduled(post_at) => {
+      let res = slack.schedule_message(msg, *post_at).await?;
+      print_warning(res.warning.as_deref());
+      let text = format!(
+        "Message scheduled, id: {}, post at: {}",
+        res.scheduled_message_id,
+        time::format_rfc3339(res.post_at)
+      );
+      Report::new(text, &res.scheduled_message_id, &res)
+    }
+    Delivery::Now => {
+      let res = slack.send_message(msg).await?;
+      print_warning(res.warning.as_deref());
+      let link = if permalink {
+        // the message is out, failing here would only hide its timestamp
+        match slack.get_permalink(&res.channel, &res.ts).await {
+          Ok(link) => Some(link.permalink),
+          Err(e) => {
+            print_warning(Some(&format!("no permalink, {e}")));
+            None
+          }
+        }
+      } else {
+        None
+      };
+      let text = format!("Message sent, timestamp: {}", res.ts);
+      match link {
+        Some(link) => Report::new(
+          format!("{text}, permalink: {link}"),
+          format!("{}\t{link}", res.ts),
+          &Sent { res: &res, permalink: &link },
+        ),
+        None => Report::new(text, &res.ts, &res),
+      }
+    }
+  }
+}
+
+/// Share the `--attach` files in one message, the text is its comment.
+async fn upload_files(args: &Cli, slack: &Client) -> Result<(), CliError> {
+  let files = args
+    .attach
+    .iter()
+    .map(|path| {
+      let name = path.file_name().unwrap_or(path.as_os_str());
+      Ok(FileUpload::new(name.to_string_lossy(), fs::read(path)?))
+    })
+    .collect::<Result<Vec<_>, io::Error>>()?;
+  // unlike a message, files can go without any text
+  let initial_comment = match args.body.get_message() {
+    Ok(text) => Some(text),
+    Err(CliError::Usage(_)) if args.body.message.is_none() => None,
+    Err(e) => return Err(e),
+  };
+  let share = Share {
+    channel_id: channel(args, slack).await?,
+    initial_comment,
+    thread_ts: args.timestamp.clone(),
+  };
+  let res = slack.upload_files(files, Some(&share)).await?;
+  print_warning(res.warning.as_deref());
+  let ids: Vec<_> = res.files.iter().map(|file| file.id.as_str()).collect();
+  let text: Vec<_> =
+    ids.iter().map(|id| format!("File uploaded, id: {id}")).collect();
+  let report = Report::new(text.join("\n"), ids.join("\n"), &res)?;
+  println!("{}", report.render(args.output));
+  Ok(())
+}
+
+async fn update_message(
+  args: &Cli, update: &UpdateArgs, slack: &Client,
+) -> Result<(), CliError> {
+  let channel = channel(args, slack).await?;
+  let msg = update.body.to_message(channel.clone())?;
+  let res = slack.update_message(&channel, &update.timestamp, &msg).await?;
+  print_warning(res.warning.as_deref());
+  let text = format!("Message updated, timestamp: {}", res.ts);
+  println!("{}", Report::new(text, &res.ts, &res)?.render(args.output));
+  Ok(())
+}
+
+async fn delete_message(
+  args: &Cli, delete: &DeleteArgs, slack: &Client,
+) -> Result<(), CliError> {
+  let channel = channel(args, slack).await?;
+  let res = slack.delete_message(&channel, &delete.timestamp).await?;
+  print_warning(res.warning.as_deref());
+  let text = format!("Message deleted, timestamp: {}", res.ts);
+  println!("{}", Report::new(text, &res.ts, &res)?.render(args.output));
+  Ok(())
+}
+
+/// Without an emoji this lists the message's reactions instead.
+async fn react_to_message(
+  args: &Cli, react: &ReactArgs, slack: &Client,
+) -> Result<(), CliError> {
+  let channel = channel(args, slack).await?;
+  let ts = &react.timestamp;
+  let Some(emoji) = &react.emoji else {
+    for reaction in slack.get_reactions(&channel, ts).await? {
+      let text = format!(
+        "{}\t{}\t{}",
+        reaction.name,
+        reaction.count,
+        reaction.users.join(",")
+      );
+      let report = Report::new(text, &reaction.name, &reaction)?;
+      println!("{}", report.render(args.output));
+    }
+    return Ok(());
+  };
+  let (res, done) = if react.remove {
+    (slack.remove_reaction(&channel, ts, emoji).await, "removed")
   } else {
-    eprintln!("{res:#?}");
-    panic!("Error: Message not sent");
+    (slack.add_reaction(&channel, ts, emoji).await, "added")
   };
+  let res = already_done(res, react.ignore_existing)?;
+  print_warning(res.warning.as_deref());
+  let text = format!("Reaction {done}, emoji: {emoji}, timestamp: {ts}");
+  println!("{}", Report::new(text, ts, &res)?.render(args.output));
   Ok(())
 }
+
+/// Print the channel's history or, with `thread`, that thread's messages,
+/// one per line.
+async fn read_messages(
+  args: &Cli, read: &ReadArgs, thread: Option<&str>, slack: &Client,
+) -> Result<(), CliError> {
+  let channel = channel(args, slack).await?;
+  let range = read.to_range();
+  let messages = match thread {
+    Some(ts) => slack.replies(&channel, ts, &range).await?,
+    None => slack.history(&channel, &range).await?,
+  };
+  for msg in messages {
+    let text =
+      format!("{}\t{}\t{}", msg.ts, msg.author(), msg.text.replace('\n', " "));
+    println!("{}", Report::new(text, &msg.ts, &msg)?.render(args.output));
+  }
+  Ok(())
+}
+
+/// Without a timestamp this lists the messages pinned to the channel, files
+/// pinned the legacy way are left out.
+async fn pin_message(
+  args: &Cli, pin: &PinArgs, slack: &Client,
+) -> Result<(), CliError> {
+  let channel = channel(args, slack).await?;
+  let Some(ts) = &pin.timestamp else {
+    for pin in slack.list_pins(&channel).await? {
+      let Some(msg) = &pin.message else { continue };
+      let text = format!(
+        "{}\t{}\t{}",
+        msg.ts,
+        pin.created_by,
+        msg.text.replace('\n', " ")
+      );
+      println!("{}", Report::new(text, &msg.ts, &pin)?.render(args.output));
+    }
+    return Ok(());
+  };
+  let (res, done) = if pin.remove {
+    (slack.unpin(&channel, ts).await, "unpinned")
+  } else {
+    (slack.pin(&channel, ts).await, "pinned")
+  };
+  let res = already_done(res, pin.ignore_existing)?;
+  print_warning(res.warning.as_deref());
+  let text = format!("Message {done}, timestamp: {ts}");
+  println!("{}", Report::new(text, ts, &res)?.render(args.output));
+  Ok(())
+}
+
+/// With `ignore_existing`, treat errors saying the message already is as
+/// asked, ex) `already_reacted`, as success with a warning so pipeline steps
+/// can rerun. Otherwise they fail like any other api error.
+fn already_done(
+  res: Result<OkResponse, SlackError>, ignore_existing: bool,
+) -> Result<OkResponse, SlackError> {
+  match res {
+    Err(SlackError::Api {
+      error:
+        error @ (ApiError::AlreadyPinned
+        | ApiError::AlreadyReacted
+        | ApiError::NoPin
+        | ApiError::NoReaction),
+      ..
+    }) if ignore_existing => Ok(OkResponse {
+      ok: true,
+      warning: Some(error.code().to_owned()),
+      response_metadata: None,
+    }),
+    res => res,
+  }
+}
+
+async fn scheduled_messages(
+  args: &Cli, schedule: &ScheduleArgs, slack: &Client,
+) -> Result<(), CliError> {
+  match &schedule.command {
+    ScheduleCommands::List => {
+      let channels = slack.resolve_channels(&args.find_channels()).await?;
+      let mut messages = if channels.is_empty() {
+        slack.list_scheduled_messages(None).await?
+      } else {
+        Vec::new()
+      };
+      for channel in &channels {
+        messages.extend(slack.list_scheduled_messages(Some(channel)).await?);
+      }
+      for msg in messages {
+        let text = format!(
+          "{}\t{}\t{}\t{}",
+          msg.id,
+          msg.channel_id,
+          time::format_rfc3339(msg.post_at),
+          msg.text.replace('\n', " ")
+        );
+        println!("{}", Report::new(text, &msg.id, &msg)?.render(args.output));
+      }
+    }
+    ScheduleCommands::Delete(delete) => {
+      let channel = channel(args, slack).await?;
+      let res = slack.delete_scheduled_message(&channel, &delete.id).await?;
+      print_warning(res.warning.as_deref());
+      let text = format!("Scheduled message deleted, id: {}", delete.id);
+      let report = Report::new(text, &delete.id, &res)?;
+      println!("{}", report.render(args.output));
+    }
+  }
+  Ok(())
+}
+
+/// The channel id to work in, names like '#deploys' are looked up.
+///
+/// With `--to-user` this is the direct message with those users.
+async fn channel(args: &Cli, slack: &Client) -> Result<String, CliError> {
+  if !args.to_user.is_empty() {
+    if args.to_user.len() > MAX_CONVERSATION_USERS {
+      return Err(CliError::Usage(format!(
+        "a group dm takes at most {MAX_CONVERSATION_USERS} users"
+      )));
+    }
+    let mut users = slack.resolve_users(&args.to_user).await?;
+    users.sort_unstable();
+    users.dedup();
+    return Ok(slack.open_conversation(&users).await?);
+  }
+  match args.get_channels()?.as_slice() {
+    [channel] => Ok(slack.resolve_channel(channel).await?),
+    _ => {
+      Err(CliError::Usage(String::from("only one channel can be given here")))
+    }
+  }
+}
+
+fn print_warning(warning: Option<&str>) {
+  if let Some(warning) = warning {
+    eprintln!("Warning: {warning}");
+  }
+}
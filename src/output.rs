@@ -0,0 +1,70 @@
+//! How results are printed, picked with `--output`.
+
+use clap::ValueEnum;
+use serde::Serialize;
+use slack_message::slack::SlackError;
+
+#[derive(ValueEnum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Output {
+  /// A sentence per result, ex) 'Message sent, timestamp: ...'.
+  #[default]
+  Text,
+  /// Slack's response as one json object per line.
+  Json,
+  /// Only the message timestamp, or the id of what has no timestamp yet.
+  TsOnly,
+}
+
+/// The outcome of one call, ready to print in any `Output`.
+#[derive(Debug)]
+pub struct Report {
+  text: String,
+  ts: String,
+  json: String,
+}
+
+impl Report {
+  pub fn new(
+    text: impl Into<String>, ts: impl Into<String>, res: &impl Serialize,
+  ) -> Result<Self, SlackError> {
+    Ok(Report {
+      text: text.into(),
+      ts: ts.into(),
+      json: serde_json::to_string(res)?,
+    })
+  }
+
+  pub fn render(&self, output: Output) -> &str {
+    match output {
+      Output::Text => &self.text,
+      Output::Json => &self.json,
+      Output::TsOnly => &self.ts,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::error::Error;
+
+  #[test]
+  fn render() -> Result<(), Box<dyn Error>> {
+    let res = serde_json::json!({ "ok": true, "ts": "1734376519.228539" });
+    let report = Report::new(
+      "Message sent, timestamp: 1734376519.228539",
+      "1734376519.228539",
+      &res,
+    )?;
+    assert_eq!(
+      "Message sent, timestamp: 1734376519.228539",
+      report.render(Output::Text)
+    );
+    assert_eq!(
+      r#"{"ok":true,"ts":"1734376519.228539"}"#,
+      report.render(Output::Json)
+    );
+    assert_eq!("1734376519.228539", report.render(Output::TsOnly));
+    Ok(())
+  }
+}
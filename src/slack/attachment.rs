@@ -0,0 +1,137 @@
+//! Legacy secondary attachments, see <https://api.slack.com/reference/messaging/attachments>.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attachment {
+  /// `good`, `warning`, `danger` or a hex color like `#439FE0`.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub color: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub fallback: Option<String>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub fields: Vec<Field>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub footer: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub pretext: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub text: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub title: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub title_link: Option<String>,
+  /// Unix time shown next to the footer.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub ts: Option<i64>,
+}
+
+impl Attachment {
+  pub fn new() -> Self {
+    Attachment::default()
+  }
+
+  #[must_use]
+  pub fn color(mut self, color: impl Into<String>) -> Self {
+    self.color = Some(color.into());
+    self
+  }
+
+  #[must_use]
+  pub fn field(mut self, field: Field) -> Self {
+    self.fields.push(field);
+    self
+  }
+
+  #[must_use]
+  pub fn footer(mut self, footer: impl Into<String>) -> Self {
+    self.footer = Some(footer.into());
+    self
+  }
+
+  #[must_use]
+  pub fn pretext(mut self, pretext: impl Into<String>) -> Self {
+    self.pretext = Some(pretext.into());
+    self
+  }
+
+  #[must_use]
+  pub fn text(mut self, text: impl Into<String>) -> Self {
+    self.text = Some(text.into());
+    self
+  }
+
+  #[must_use]
+  pub fn title(mut self, title: impl Into<String>) -> Self {
+    self.title = Some(title.into());
+    self
+  }
+
+  #[must_use]
+  pub fn title_link(mut self, title_link: impl Into<String>) -> Self {
+    self.title_link = Some(title_link.into());
+    self
+  }
+
+  #[must_use]
+  pub fn ts(mut self, ts: i64) -> Self {
+    self.ts = Some(ts);
+    self
+  }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Field {
+  pub title: String,
+  pub value: String,
+  /// Short fields are laid out side by side.
+  #[serde(default)]
+  pub short: bool,
+}
+
+impl Field {
+  pub fn new(title: impl Into<String>, value: impl Into<String>) -> Self {
+    Field { title: title.into(), value: value.into(), short: true }
+  }
+}
+
+/// Whether slack will accept `color`, ex) `danger` or `#36a64f`.
+pub fn is_valid_color(color: &str) -> bool {
+  match color.strip_prefix('#') {
+    Some(hex) => {
+      matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit())
+    }
+    None => matches!(color, "good" | "warning" | "danger"),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::error::Error;
+
+  #[test]
+  fn serialize_attachment() -> Result<(), Box<dyn Error>> {
+    let attachment = Attachment::new()
+      .color("danger")
+      .title("Build failed")
+      .title_link("https://ci.example.com/1234")
+      .field(Field::new("Build", "1234"))
+      .footer("ci")
+      .ts(1_734_376_519);
+    let actual = serde_json::to_string(&attachment)?;
+    let expected = r#"{"color":"danger","fields":[{"title":"Build","value":"1234","short":true}],"footer":"ci","title":"Build failed","title_link":"https://ci.example.com/1234","ts":1734376519}"#;
+    assert_eq!(expected, actual, "\nexpected: {expected}\nactual:{actual}");
+    Ok(())
+  }
+
+  #[test]
+  fn valid_colors() {
+    for color in ["good", "warning", "danger", "#36a64f", "#FFF"] {
+      assert!(is_valid_color(color), "{color}");
+    }
+    for color in ["red", "", "#", "#12345", "#GGGGGG", "36a64f"] {
+      assert!(!is_valid_color(color), "{color}");
+    }
+  }
+}
@@ -0,0 +1,504 @@
+//! Typed Block Kit layout blocks, see <https://api.slack.com/reference/block-kit/blocks>.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub const MAX_BLOCKS: usize = 50;
+pub const MAX_SECTION_TEXT: usize = 3000;
+pub const MAX_SECTION_FIELDS: usize = 10;
+pub const MAX_SECTION_FIELD_TEXT: usize = 2000;
+pub const MAX_HEADER_TEXT: usize = 150;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Block {
+  Actions {
+    elements: Vec<Element>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    block_id: Option<String>,
+  },
+  Context {
+    elements: Vec<ContextElement>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    block_id: Option<String>,
+  },
+  Divider {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    block_id: Option<String>,
+  },
+  File {
+    external_id: String,
+    source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    block_id: Option<String>,
+  },
+  Header {
+    text: Text,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    block_id: Option<String>,
+  },
+  Image {
+    image_url: String,
+    alt_text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<Text>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    block_id: Option<String>,
+  },
+  Input {
+    label: Text,
+    element: Element,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hint: Option<Text>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    optional: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    block_id: Option<String>,
+  },
+  RichText {
+    elements: Vec<RichTextElement>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    block_id: Option<String>,
+  },
+  Section {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<Text>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fields: Option<Vec<Text>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    accessory: Option<Element>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expand: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    block_id: Option<String>,
+  },
+  Video {
+    alt_text: String,
+    title: Text,
+    thumbnail_url: String,
+    video_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    block_id: Option<String>,
+  },
+  /// Block Kit json sent exactly as given, see [`parse_blocks`].
+  #[serde(untagged, skip_deserializing)]
+  Raw(Value),
+}
+
+impl Block {
+  pub fn divider() -> Self {
+    Block::Divider { block_id: None }
+  }
+
+  pub fn header(text: impl Into<String>) -> Self {
+    Block::Header { text: Text::plain(text), block_id: None }
+  }
+
+  pub fn section(text: impl Into<String>) -> Self {
+    Block::Section {
+      text: Some(Text::mrkdwn(text)),
+      fields: None,
+      accessory: None,
+      expand: None,
+      block_id: None,
+    }
+  }
+
+  pub fn context(text: impl Into<String>) -> Self {
+    Block::Context {
+      elements: vec![ContextElement::Mrkdwn {
+        text: text.into(),
+        verbatim: None,
+      }],
+      block_id: None,
+    }
+  }
+}
+
+/// Text composition object.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Text {
+  PlainText {
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    emoji: Option<bool>,
+  },
+  Mrkdwn {
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    verbatim: Option<bool>,
+  },
+}
+
+impl Text {
+  pub fn plain(text: impl Into<String>) -> Self {
+    Text::PlainText { text: text.into(), emoji: None }
+  }
+
+  pub fn mrkdwn(text: impl Into<String>) -> Self {
+    Text::Mrkdwn { text: text.into(), verbatim: None }
+  }
+
+  pub fn text(&self) -> &str {
+    match self {
+      Text::PlainText { text, .. } | Text::Mrkdwn { text, .. } => text,
+    }
+  }
+}
+
+/// Interactive elements of `actions` blocks and section accessories.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Element {
+  Button {
+    text: Text,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    action_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    style: Option<ButtonStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    confirm: Option<Confirm>,
+  },
+  Datepicker {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    action_id: Option<String>,
+    /// `YYYY-MM-DD`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    initial_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    placeholder: Option<Text>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    confirm: Option<Confirm>,
+  },
+  Image {
+    image_url: String,
+    alt_text: String,
+  },
+  Overflow {
+    options: Vec<SelectOption>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    action_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    confirm: Option<Confirm>,
+  },
+  PlainTextInput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    action_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    multiline: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    placeholder: Option<Text>,
+  },
+  StaticSelect {
+    options: Vec<SelectOption>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    placeholder: Option<Text>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    action_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    confirm: Option<Confirm>,
+  },
+}
+
+/// Dialog asking to confirm an element's action, ex) before a rollback.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Confirm {
+  pub title: Text,
+  pub text: Text,
+  pub confirm: Text,
+  pub deny: Text,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub style: Option<ButtonStyle>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ButtonStyle {
+  Primary,
+  Danger,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SelectOption {
+  pub text: Text,
+  pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContextElement {
+  Image {
+    image_url: String,
+    alt_text: String,
+  },
+  PlainText {
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    emoji: Option<bool>,
+  },
+  Mrkdwn {
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    verbatim: Option<bool>,
+  },
+}
+
+/// Top level elements of a `rich_text` block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RichTextElement {
+  RichTextSection {
+    elements: Vec<RichTextInline>,
+  },
+  RichTextList {
+    style: ListStyle,
+    elements: Vec<RichTextElement>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    indent: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offset: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    border: Option<u8>,
+  },
+  RichTextPreformatted {
+    elements: Vec<RichTextInline>,
+  },
+  RichTextQuote {
+    elements: Vec<RichTextInline>,
+  },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ListStyle {
+  Bullet,
+  Ordered,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RichTextInline {
+  /// `@here`, `@channel` or `@everyone`.
+  Broadcast {
+    range: String,
+  },
+  Channel {
+    channel_id: String,
+  },
+  Color {
+    value: String,
+  },
+  Date {
+    timestamp: i64,
+    format: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fallback: Option<String>,
+  },
+  Emoji {
+    name: String,
+  },
+  Link {
+    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    style: Option<TextStyle>,
+  },
+  Text {
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    style: Option<TextStyle>,
+  },
+  User {
+    user_id: String,
+  },
+  Usergroup {
+    usergroup_id: String,
+  },
+}
+
+#[derive(
+  Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub struct TextStyle {
+  #[serde(default, skip_serializing_if = "is_false")]
+  pub bold: bool,
+  #[serde(default, skip_serializing_if = "is_false")]
+  pub italic: bool,
+  #[serde(default, skip_serializing_if = "is_false")]
+  pub strike: bool,
+  #[serde(default, skip_serializing_if = "is_false")]
+  pub code: bool,
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_false(b: &bool) -> bool {
+  !b
+}
+
+/// Parse either a bare block array or a Block Kit Builder `{"blocks":[...]}`.
+///
+/// Blocks are kept as the given json so every block and element type slack
+/// has, modelled here or not, reaches slack as written. Only a `type` is
+/// required, [`validate`] checks the limits.
+pub fn parse_blocks(json: &str) -> Result<Vec<Block>, serde_json::Error> {
+  #[derive(Deserialize)]
+  struct Payload {
+    blocks: Vec<Value>,
+  }
+
+  let blocks = if json.trim_start().starts_with('[') {
+    serde_json::from_str(json)?
+  } else {
+    serde_json::from_str::<Payload>(json)?.blocks
+  };
+  if let Some(i) = blocks.iter().position(|b| !b["type"].is_string()) {
+    return Err(serde::de::Error::custom(format!("block {i} has no type")));
+  }
+  Ok(blocks.into_iter().map(Block::Raw).collect())
+}
+
+/// Check slack's documented block limits, returning the first violation.
+///
+/// Works on the json of each block, so blocks from [`parse_blocks`] are
+/// checked the same as typed ones.
+pub fn validate(blocks: &[Block]) -> Result<(), String> {
+  if blocks.len() > MAX_BLOCKS {
+    return Err(format!(
+      "{} blocks exceeds the limit of {MAX_BLOCKS}",
+      blocks.len()
+    ));
+  }
+  for (i, block) in blocks.iter().enumerate() {
+    let json =
+      serde_json::to_value(block).map_err(|e| format!("block {i}: {e}"))?;
+    match json["type"].as_str() {
+      Some("section") => {
+        if let Some(text) = json["text"]["text"].as_str() {
+          check_len(i, "section text", text, MAX_SECTION_TEXT)?;
+        }
+        let fields = json["fields"].as_array().map_or(&[][..], Vec::as_slice);
+        if fields.len() > MAX_SECTION_FIELDS {
+          return Err(format!(
+            "block {i}: {} section fields exceeds the limit of {MAX_SECTION_FIELDS}",
+            fields.len()
+          ));
+        }
+        for field in fields.iter().filter_map(|f| f["text"].as_str()) {
+          check_len(i, "section field", field, MAX_SECTION_FIELD_TEXT)?;
+        }
+      }
+      Some("header") => {
+        if let Some(text) = json["text"]["text"].as_str() {
+          check_len(i, "header text", text, MAX_HEADER_TEXT)?;
+        }
+      }
+      _ => {}
+    }
+  }
+  Ok(())
+}
+
+fn check_len(
+  block: usize, what: &str, text: &str, max: usize,
+) -> Result<(), String> {
+  let len = text.chars().count();
+  if len > max {
+    return Err(format!(
+      "block {block}: {what} is {len} characters, the limit is {max}"
+    ));
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::error::Error;
+
+  #[test]
+  fn serialize_blocks() -> Result<(), Box<dyn Error>> {
+    let blocks = vec![
+      Block::header("Release 1.2.3"),
+      Block::divider(),
+      Block::section("*done*"),
+      Block::context("by ci"),
+    ];
+    let actual = serde_json::to_string(&blocks)?;
+    let expected = r#"[{"type":"header","text":{"type":"plain_text","text":"Release 1.2.3"}},{"type":"divider"},{"type":"section","text":{"type":"mrkdwn","text":"*done*"}},{"type":"context","elements":[{"type":"mrkdwn","text":"by ci"}]}]"#;
+    assert_eq!(expected, actual, "\nexpected: {expected}\nactual:{actual}");
+    Ok(())
+  }
+
+  #[test]
+  fn parse_blocks_round_trip() -> Result<(), Box<dyn Error>> {
+    let json = r#"{"blocks":[{"type":"actions","elements":[{"type":"button","text":{"type":"plain_text","text":"Open"},"url":"https://example.com","style":"primary"}]},{"type":"image","image_url":"https://example.com/a.png","alt_text":"a"},{"type":"rich_text","elements":[{"type":"rich_text_list","style":"bullet","elements":[{"type":"rich_text_section","elements":[{"type":"text","text":"fix","style":{"bold":true}},{"type":"emoji","name":"tada"}]}]}]}]}"#;
+    let blocks = parse_blocks(json)?;
+    assert_eq!(3, blocks.len());
+    let reparsed = parse_blocks(&serde_json::to_string(&blocks)?)?;
+    assert_eq!(blocks, reparsed);
+    Ok(())
+  }
+
+  #[test]
+  fn parse_blocks_keeps_unknown_fields() -> Result<(), Box<dyn Error>> {
+    let json = r##"[{"type":"section","text":{"type":"mrkdwn","text":"Deploy v1.2.3?"},"expand":true,"accessory":{"type":"button","text":{"type":"plain_text","text":"Rollback"},"style":"danger","confirm":{"title":{"type":"plain_text","text":"Sure?"},"text":{"type":"mrkdwn","text":"Roll back prod"},"confirm":{"type":"plain_text","text":"Yes"},"deny":{"type":"plain_text","text":"No"}}}},{"type":"actions","elements":[{"type":"datepicker","action_id":"day","focus_on_load":false}]},{"type":"rich_text","elements":[{"type":"rich_text_list","style":"bullet","border":1,"offset":0,"elements":[{"type":"rich_text_section","elements":[{"type":"broadcast","range":"here"},{"type":"usergroup","usergroup_id":"S1"},{"type":"date","timestamp":1734376519,"format":"{date_short}"},{"type":"color","value":"#36a64f"}]}]}]}]"##;
+    let blocks = parse_blocks(json)?;
+    assert!(validate(&blocks).is_ok());
+    let expected: Value = serde_json::from_str(json)?;
+    assert_eq!(expected, serde_json::to_value(&blocks)?);
+    Ok(())
+  }
+
+  #[test]
+  fn validate_raw_limits() -> Result<(), Box<dyn Error>> {
+    let header = "a".repeat(MAX_HEADER_TEXT + 1);
+    let json = format!(
+      r#"[{{"type":"header","text":{{"type":"plain_text","text":"{header}"}}}}]"#
+    );
+    assert!(validate(&parse_blocks(&json)?).is_err());
+    let field = r#"{"type":"mrkdwn","text":"a"}"#;
+    let fields = [field; MAX_SECTION_FIELDS + 1].join(",");
+    let json = format!(r#"[{{"type":"section","fields":[{fields}]}}]"#);
+    assert!(validate(&parse_blocks(&json)?).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn parse_blocks_unmodelled_types() -> Result<(), Box<dyn Error>> {
+    let json = r#"[{"type":"actions","elements":[{"type":"users_select","action_id":"a"},{"type":"timepicker"}]},{"type":"markdown","text":"hi"}]"#;
+    let blocks = parse_blocks(json)?;
+    let expected: Value = serde_json::from_str(json)?;
+    assert_eq!(expected, serde_json::to_value(&blocks)?);
+    Ok(())
+  }
+
+  #[test]
+  fn parse_blocks_no_type() {
+    let actual = parse_blocks(r#"[{"type":"divider"},{"text":"hi"}]"#);
+    assert_eq!("block 1 has no type", actual.unwrap_err().to_string());
+    assert!(parse_blocks(r#"[{"type":"divider"}"#).is_err());
+  }
+
+  #[test]
+  fn validate_limits() {
+    assert!(validate(&[Block::section("ok")]).is_ok());
+    assert!(validate(&vec![Block::divider(); MAX_BLOCKS]).is_ok());
+    assert!(validate(&vec![Block::divider(); MAX_BLOCKS + 1]).is_err());
+    let long = "a".repeat(MAX_SECTION_TEXT + 1);
+    let actual = validate(&[Block::divider(), Block::section(long)]);
+    assert_eq!(
+      Err(String::from(
+        "block 1: section text is 3001 characters, the limit is 3000"
+      )),
+      actual
+    );
+    let header = "a".repeat(MAX_HEADER_TEXT + 1);
+    assert!(validate(&[Block::header(header)]).is_err());
+  }
+}
@@ -0,0 +1,402 @@
+use super::response::{OkResponse, ResponseMetadata};
+use super::{Attachment, Block, Client, Message, SlackError};
+use crate::globals::{
+  CHAT_DELETE, CHAT_DELETE_SCHEDULED_MESSAGE, CHAT_GET_PERMALINK,
+  CHAT_POST_EPHEMERAL, CHAT_SCHEDULED_MESSAGES_LIST, CHAT_SCHEDULE_MESSAGE,
+  CHAT_UPDATE,
+};
+use serde::{Deserialize, Serialize};
+
+/// Body of a `chat.delete` call.
+#[derive(Debug, Serialize)]
+struct Delete<'a> {
+  channel: &'a str,
+  ts: &'a str,
+}
+
+/// Body returned by a successful `chat.delete`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeleteResponse {
+  pub ok: bool,
+  pub channel: String,
+  pub ts: String,
+  pub warning: Option<String>,
+  pub response_metadata: Option<ResponseMetadata>,
+}
+
+/// Body of a `chat.getPermalink` call.
+#[derive(Debug, Serialize)]
+struct GetPermalink<'a> {
+  channel: &'a str,
+  message_ts: &'a str,
+}
+
+/// Body returned by a successful `chat.getPermalink`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PermalinkResponse {
+  pub ok: bool,
+  pub channel: String,
+  pub permalink: String,
+  pub warning: Option<String>,
+  pub response_metadata: Option<ResponseMetadata>,
+}
+
+/// Body of a `chat.update` call.
+#[derive(Debug, Serialize)]
+struct Update<'a> {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  attachments: Option<&'a [Attachment]>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  blocks: Option<&'a [Block]>,
+  channel: &'a str,
+  #[serde(skip_serializing_if = "str::is_empty")]
+  text: &'a str,
+  ts: &'a str,
+}
+
+/// Body returned by a successful `chat.update`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateResponse {
+  pub ok: bool,
+  pub channel: String,
+  pub ts: String,
+  #[serde(default)]
+  pub text: String,
+  pub warning: Option<String>,
+  pub response_metadata: Option<ResponseMetadata>,
+}
+
+/// Body of a `chat.postEphemeral` call.
+#[derive(Debug, Serialize)]
+struct Ephemeral<'a> {
+  #[serde(flatten)]
+  message: &'a Message,
+  user: &'a str,
+}
+
+/// Body returned by a successful `chat.postEphemeral`.
+///
+/// Ephemeral messages aren't stored, so only their timestamp comes back.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EphemeralResponse {
+  pub ok: bool,
+  pub message_ts: String,
+  pub warning: Option<String>,
+  pub response_metadata: Option<ResponseMetadata>,
+}
+
+/// Body of a `chat.scheduleMessage` call.
+#[derive(Debug, Serialize)]
+struct Schedule<'a> {
+  #[serde(flatten)]
+  message: &'a Message,
+  post_at: i64,
+}
+
+/// Body returned by a successful `chat.scheduleMessage`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScheduleResponse {
+  pub ok: bool,
+  pub channel: String,
+  pub scheduled_message_id: String,
+  pub post_at: i64,
+  pub warning: Option<String>,
+  pub response_metadata: Option<ResponseMetadata>,
+}
+
+/// Body of a `chat.scheduledMessages.list` call.
+#[derive(Debug, Serialize)]
+struct ScheduledList<'a> {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  channel: Option<&'a str>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  cursor: Option<&'a str>,
+  limit: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScheduledListResponse {
+  scheduled_messages: Vec<ScheduledMessage>,
+  response_metadata: Option<ResponseMetadata>,
+}
+
+/// A message waiting to be posted, as listed by `chat.scheduledMessages.list`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduledMessage {
+  pub id: String,
+  pub channel_id: String,
+  pub post_at: i64,
+  pub date_created: i64,
+  #[serde(default)]
+  pub text: String,
+}
+
+/// Body of a `chat.deleteScheduledMessage` call.
+#[derive(Debug, Serialize)]
+struct DeleteScheduled<'a> {
+  channel: &'a str,
+  scheduled_message_id: &'a str,
+}
+
+impl Client {
+  /// Delete the message at `ts` in `channel`.
+  pub async fn delete_message(
+    &self, channel: &str, ts: &str,
+  ) -> Result<DeleteResponse, SlackError> {
+    self.post(CHAT_DELETE, &Delete { channel, ts }).await
+  }
+
+  /// Url of the message at `ts` in `channel`, ex)
+  /// `https://example.slack.com/archives/C1/p1734376519228539`.
+  pub async fn get_permalink(
+    &self, channel: &str, ts: &str,
+  ) -> Result<PermalinkResponse, SlackError> {
+    let body = GetPermalink { channel, message_ts: ts };
+    self.post_form(CHAT_GET_PERMALINK, &body).await
+  }
+
+  /// Post `message` so only `user` sees it in the message's channel.
+  pub async fn post_ephemeral(
+    &self, message: &Message, user: &str,
+  ) -> Result<EphemeralResponse, SlackError> {
+    message.validate()?;
+    self.post(CHAT_POST_EPHEMERAL, &Ephemeral { message, user }).await
+  }
+
+  /// Queue `message` to be posted at unix time `post_at`.
+  pub async fn schedule_message(
+    &self, message: &Message, post_at: i64,
+  ) -> Result<ScheduleResponse, SlackError> {
+    message.validate()?;
+    self.post(CHAT_SCHEDULE_MESSAGE, &Schedule { message, post_at }).await
+  }
+
+  /// All messages still waiting to be posted, optionally only in `channel`.
+  pub async fn list_scheduled_messages(
+    &self, channel: Option<&str>,
+  ) -> Result<Vec<ScheduledMessage>, SlackError> {
+    let mut scheduled = Vec::new();
+    let mut cursor = None;
+    loop {
+      let list =
+        ScheduledList { channel, cursor: cursor.as_deref(), limit: 100 };
+      let page: ScheduledListResponse =
+        self.post(CHAT_SCHEDULED_MESSAGES_LIST, &list).await?;
+      scheduled.extend(page.scheduled_messages);
+      cursor = ResponseMetadata::next_cursor(page.response_metadata.as_ref());
+      if cursor.is_none() {
+        return Ok(scheduled);
+      }
+    }
+  }
+
+  /// Cancel a message queued with `schedule_message`.
+  pub async fn delete_scheduled_message(
+    &self, channel: &str, scheduled_message_id: &str,
+  ) -> Result<OkResponse, SlackError> {
+    let body = DeleteScheduled { channel, scheduled_message_id };
+    self.post(CHAT_DELETE_SCHEDULED_MESSAGE, &body).await
+  }
+
+  /// Replace the text, blocks and attachments of the message at `ts` in
+  /// `channel` with those of `message`.
+  pub async fn update_message(
+    &self, channel: &str, ts: &str, message: &Message,
+  ) -> Result<UpdateResponse, SlackError> {
+    message.validate()?;
+    let update = Update {
+      attachments: message.attachments.as_deref(),
+      blocks: message.blocks.as_deref(),
+      channel,
+      text: &message.text,
+      ts,
+    };
+    self.post(CHAT_UPDATE, &update).await
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::slack::mock;
+  use crate::slack::ApiError;
+  use std::error::Error;
+  use wiremock::matchers::{body_json, body_string};
+  use wiremock::MockServer;
+
+  #[tokio::test]
+  async fn delete_message_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    let expected_body =
+      serde_json::json!({ "channel": "C1", "ts": "1734376519.228539" });
+    let body = r#"{"ok":true,"channel":"C1","ts":"1734376519.228539"}"#;
+    mock::given("chat.delete")
+      .and(body_json(expected_body))
+      .respond_with(mock::json(body))
+      .mount(&mock_server)
+      .await;
+    let client = mock::client(&mock_server);
+    let actual = client.delete_message("C1", "1734376519.228539").await?;
+    assert_eq!("C1", actual.channel);
+    assert_eq!("1734376519.228539", actual.ts);
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn delete_message_cant_delete_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    let body = r#"{"ok":false,"error":"cant_delete_message"}"#;
+    mock::mount_json(&mock_server, "chat.delete", body).await;
+    let client = mock::client(&mock_server);
+    match client.delete_message("C1", "1.2").await {
+      Err(SlackError::Api { error, .. }) => {
+        assert_eq!(ApiError::CantDeleteMessage, error);
+      }
+      other => panic!("expected api error, got: {other:?}"),
+    }
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn get_permalink_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    let body = r#"{"ok":true,"channel":"C1","permalink":"https://example.slack.com/archives/C1/p1734376519228539"}"#;
+    mock::given("chat.getPermalink")
+      .and(body_string("channel=C1&message_ts=1734376519.228539"))
+      .respond_with(mock::json(body))
+      .mount(&mock_server)
+      .await;
+    let client = mock::client(&mock_server);
+    let actual = client.get_permalink("C1", "1734376519.228539").await?;
+    assert_eq!(
+      "https://example.slack.com/archives/C1/p1734376519228539",
+      actual.permalink
+    );
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn post_ephemeral_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    let expected_body = serde_json::json!({
+      "channel": "C1",
+      "text": "your PR build failed",
+      "user": "U1",
+    });
+    let body = r#"{"ok":true,"message_ts":"1502210682.580145"}"#;
+    mock::given("chat.postEphemeral")
+      .and(body_json(expected_body))
+      .respond_with(mock::json(body))
+      .mount(&mock_server)
+      .await;
+    let client = mock::client(&mock_server);
+    let msg = Message::new("C1", "your PR build failed");
+    let actual = client.post_ephemeral(&msg, "U1").await?;
+    assert_eq!("1502210682.580145", actual.message_ts);
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn schedule_message_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    let expected_body = serde_json::json!({
+      "channel": "C1",
+      "text": "freeze starts in 1 hour",
+      "post_at": 1_738_342_800,
+    });
+    let body = r#"{"ok":true,"channel":"C1","scheduled_message_id":"Q1298393284","post_at":1738342800,"message":{"type":"delayed_message","text":"freeze starts in 1 hour"}}"#;
+    mock::given("chat.scheduleMessage")
+      .and(body_json(expected_body))
+      .respond_with(mock::json(body))
+      .mount(&mock_server)
+      .await;
+    let client = mock::client(&mock_server);
+    let msg = Message::new("C1", "freeze starts in 1 hour");
+    let actual = client.schedule_message(&msg, 1_738_342_800).await?;
+    assert_eq!("Q1298393284", actual.scheduled_message_id);
+    assert_eq!(1_738_342_800, actual.post_at);
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn list_scheduled_messages_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    let route = "chat.scheduledMessages.list";
+    let page1 = r#"{"ok":true,"scheduled_messages":[{"id":"Q1","channel_id":"C1","post_at":1738342800,"date_created":1738339200,"text":"one"}],"response_metadata":{"next_cursor":"page2"}}"#;
+    let page2 = r#"{"ok":true,"scheduled_messages":[{"id":"Q2","channel_id":"C1","post_at":1738346400,"date_created":1738339200,"text":"two"}],"response_metadata":{"next_cursor":""}}"#;
+    mock::given(route)
+      .and(body_json(serde_json::json!({ "channel": "C1", "limit": 100 })))
+      .respond_with(mock::json(page1))
+      .mount(&mock_server)
+      .await;
+    mock::given(route)
+      .and(body_json(
+        serde_json::json!({ "channel": "C1", "cursor": "page2", "limit": 100 }),
+      ))
+      .respond_with(mock::json(page2))
+      .mount(&mock_server)
+      .await;
+    let client = mock::client(&mock_server);
+    let actual = client.list_scheduled_messages(Some("C1")).await?;
+    let ids = actual.iter().map(|m| m.id.as_str()).collect::<Vec<&str>>();
+    assert_eq!(vec!["Q1", "Q2"], ids);
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn delete_scheduled_message_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    let expected_body = serde_json::json!({
+      "channel": "C1",
+      "scheduled_message_id": "Q1298393284",
+    });
+    mock::given("chat.deleteScheduledMessage")
+      .and(body_json(expected_body))
+      .respond_with(mock::json(r#"{"ok":true}"#))
+      .mount(&mock_server)
+      .await;
+    let client = mock::client(&mock_server);
+    let actual = client.delete_scheduled_message("C1", "Q1298393284").await?;
+    assert!(actual.ok);
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn update_message_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    let expected_body = serde_json::json!({
+      "channel": "C1",
+      "text": "deploy finished",
+      "ts": "1734376519.228539",
+    });
+    let body = r#"{"ok":true,"channel":"C1","ts":"1734376519.228539","text":"deploy finished","message":{"type":"message","text":"deploy finished"}}"#;
+    mock::given("chat.update")
+      .and(body_json(expected_body))
+      .respond_with(mock::json(body))
+      .mount(&mock_server)
+      .await;
+    let client = mock::client(&mock_server);
+    let msg = Message::new("ignored", "deploy finished");
+    let actual = client.update_message("C1", "1734376519.228539", &msg).await?;
+    assert_eq!("C1", actual.channel);
+    assert_eq!("1734376519.228539", actual.ts);
+    assert_eq!("deploy finished", actual.text);
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn update_message_not_found_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    let body = r#"{"ok":false,"error":"message_not_found"}"#;
+    mock::mount_json(&mock_server, "chat.update", body).await;
+    let client = mock::client(&mock_server);
+    let msg = Message::new("C1", "deploy finished");
+    match client.update_message("C1", "1.2", &msg).await {
+      Err(SlackError::Api { error, .. }) => {
+        assert_eq!("message_not_found", error.code());
+      }
+      other => panic!("expected api error, got: {other:?}"),
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,433 @@
+use super::lookup::close_matches;
+use super::response::{Message, ResponseMetadata};
+use super::{Client, SlackError};
+use crate::globals::{
+  CONVERSATIONS_HISTORY, CONVERSATIONS_LIST, CONVERSATIONS_OPEN,
+  CONVERSATIONS_REPLIES,
+};
+use serde::{Deserialize, Serialize};
+
+/// Most users `conversations.open` takes, not counting the caller.
+pub const MAX_CONVERSATION_USERS: usize = 8;
+
+/// A channel as listed by `conversations.list`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Channel {
+  pub id: String,
+  #[serde(default)]
+  pub name: String,
+  #[serde(default)]
+  pub is_archived: bool,
+  #[serde(default)]
+  pub is_private: bool,
+}
+
+/// Body of a `conversations.list` call.
+#[derive(Debug, Serialize)]
+struct List<'a> {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  cursor: Option<&'a str>,
+  exclude_archived: bool,
+  limit: u32,
+  types: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListResponse {
+  channels: Vec<Channel>,
+  response_metadata: Option<ResponseMetadata>,
+}
+
+/// Which messages to read, ex) at most 50 since `oldest`.
+///
+/// `oldest` and `latest` are message timestamps, both inclusive.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct Range {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub oldest: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub latest: Option<String>,
+  /// Most messages to return, all of them when none.
+  #[serde(skip)]
+  pub limit: Option<usize>,
+}
+
+/// Body of a `conversations.history` or `conversations.replies` call.
+#[derive(Debug, Serialize)]
+struct History<'a> {
+  channel: &'a str,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  cursor: Option<&'a str>,
+  /// So `oldest` and `latest` are included like `Range` says.
+  inclusive: bool,
+  limit: usize,
+  #[serde(flatten)]
+  range: &'a Range,
+  /// The thread's parent, only for `conversations.replies`.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  ts: Option<&'a str>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HistoryResponse {
+  messages: Vec<Message>,
+  response_metadata: Option<ResponseMetadata>,
+}
+
+/// Body of a `conversations.open` call.
+#[derive(Debug, Serialize)]
+struct Open {
+  /// Comma separated user ids.
+  users: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenResponse {
+  channel: Channel,
+}
+
+/// Whether `channel` already is a channel id rather than a name.
+///
+/// Ids are upper case, channel names can't be.
+pub fn is_channel_id(channel: &str) -> bool {
+  channel.len() >= 9
+    && channel.starts_with(['C', 'D', 'G'])
+    && channel.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+}
+
+impl Client {
+  /// Every public and private channel the token can see, archived excluded.
+  pub async fn list_channels(&self) -> Result<Vec<Channel>, SlackError> {
+    let mut channels = Vec::new();
+    let mut cursor = None;
+    loop {
+      let list = List {
+        cursor: cursor.as_deref(),
+        exclude_archived: true,
+        limit: 1000,
+        types: "public_channel,private_channel",
+      };
+      let page: ListResponse =
+        self.post_form(CONVERSATIONS_LIST, &list).await?;
+      channels.extend(page.channels);
+      cursor = ResponseMetadata::next_cursor(page.response_metadata.as_ref());
+      if cursor.is_none() {
+        return Ok(channels);
+      }
+    }
+  }
+
+  /// Resolve `#name` or `name` to a channel id, ids are returned as is.
+  pub async fn resolve_channel(
+    &self, channel: &str,
+  ) -> Result<String, SlackError> {
+    let mut ids = self.resolve_channels(&[channel]).await?;
+    Ok(ids.remove(0))
+  }
+
+  /// Resolve each of `channels` like [`Client::resolve_channel`], listing
+  /// the workspace at most once.
+  pub async fn resolve_channels(
+    &self, channels: &[impl AsRef<str>],
+  ) -> Result<Vec<String>, SlackError> {
+    let mut known = None;
+    let mut ids = Vec::with_capacity(channels.len());
+    for channel in channels.iter().map(AsRef::as_ref) {
+      if is_channel_id(channel) {
+        ids.push(channel.to_owned());
+        continue;
+      }
+      if known.is_none() {
+        known = Some(self.list_channels().await?);
+      }
+      let name = channel.strip_prefix('#').unwrap_or(channel);
+      ids.push(find_channel(name, known.as_deref().unwrap_or_default())?);
+    }
+    Ok(ids)
+  }
+
+  /// Messages posted in `channel`, newest first, thread replies left out.
+  pub async fn history(
+    &self, channel: &str, range: &Range,
+  ) -> Result<Vec<Message>, SlackError> {
+    self.read_messages(CONVERSATIONS_HISTORY, channel, None, range).await
+  }
+
+  /// The thread started by the message at `ts`, parent first and then its
+  /// replies oldest first.
+  pub async fn replies(
+    &self, channel: &str, ts: &str, range: &Range,
+  ) -> Result<Vec<Message>, SlackError> {
+    self.read_messages(CONVERSATIONS_REPLIES, channel, Some(ts), range).await
+  }
+
+  /// Page through `method` until `range.limit` messages or the last page.
+  async fn read_messages(
+    &self, method: &str, channel: &str, ts: Option<&str>, range: &Range,
+  ) -> Result<Vec<Message>, SlackError> {
+    let mut messages = Vec::new();
+    let mut cursor = None;
+    loop {
+      let left = range.limit.map_or(usize::MAX, |l| l - messages.len());
+      if left == 0 {
+        return Ok(messages);
+      }
+      let history = History {
+        channel,
+        cursor: cursor.as_deref(),
+        inclusive: true,
+        // slack suggests pages of no more than 200
+        limit: left.min(200),
+        range,
+        ts,
+      };
+      let page: HistoryResponse = self.post_form(method, &history).await?;
+      // replies repeats the thread's parent at the top of every page
+      let repeated =
+        |m: &Message| cursor.is_some() && Some(m.ts.as_str()) == ts;
+      messages
+        .extend(page.messages.into_iter().filter(|m| !repeated(m)).take(left));
+      cursor = ResponseMetadata::next_cursor(page.response_metadata.as_ref());
+      if cursor.is_none() {
+        return Ok(messages);
+      }
+    }
+  }
+
+  /// Open, or reopen, the direct message with `users` and return its id.
+  ///
+  /// More than one user opens a multi person direct message.
+  pub async fn open_conversation(
+    &self, users: &[String],
+  ) -> Result<String, SlackError> {
+    let open = Open { users: users.join(",") };
+    let res: OpenResponse = self.post(CONVERSATIONS_OPEN, &open).await?;
+    Ok(res.channel.id)
+  }
+}
+
+/// The id of the one channel named `name`.
+fn find_channel(
+  name: &str, channels: &[Channel],
+) -> Result<String, SlackError> {
+  let mut matches = channels.iter().filter(|c| c.name == name);
+  match (matches.next(), matches.next()) {
+    (Some(found), None) => Ok(found.id.clone()),
+    (Some(first), Some(second)) => {
+      let ids = [first, second].into_iter().chain(matches);
+      Err(SlackError::Ambiguous {
+        kind: "channel",
+        name: name.to_owned(),
+        matches: ids.map(|c| c.id.clone()).collect(),
+      })
+    }
+    (None, _) => Err(SlackError::NotFound {
+      kind: "channel",
+      name: name.to_owned(),
+      close_matches: close_matches(
+        name,
+        channels.iter().map(|c| c.name.as_str()),
+      ),
+    }),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::slack::mock;
+  use std::error::Error;
+  use wiremock::matchers::{body_json, body_string, body_string_contains};
+  use wiremock::MockServer;
+
+  #[test]
+  fn channel_ids() {
+    assert!(is_channel_id("C12345ABCDE"));
+    assert!(is_channel_id("G0123456789"));
+    assert!(!is_channel_id("deploys"));
+    assert!(!is_channel_id("#deploys"));
+    assert!(!is_channel_id("C123"));
+    assert!(!is_channel_id("c12345abcde"));
+  }
+
+  async fn setup_mock_server() -> Result<MockServer, Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    let route = "conversations.list";
+    let page1 = r#"{"ok":true,"channels":[{"id":"C0000000001","name":"general"},{"id":"C0000000002","name":"deploys"}],"response_metadata":{"next_cursor":"page2"}}"#;
+    let page2 = r#"{"ok":true,"channels":[{"id":"G0000000003","name":"deploy-prod","is_private":true},{"id":"C0000000004","name":"dup"},{"id":"C0000000005","name":"dup"}],"response_metadata":{"next_cursor":""}}"#;
+    mock::given(route)
+      .and(body_string_contains("cursor=page2"))
+      .respond_with(mock::json(page2))
+      .mount(&mock_server)
+      .await;
+    mock::given(route)
+      .and(body_string_contains("types=public_channel%2Cprivate_channel"))
+      .respond_with(mock::json(page1))
+      .mount(&mock_server)
+      .await;
+    Ok(mock_server)
+  }
+
+  #[tokio::test]
+  async fn list_channels_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = setup_mock_server().await?;
+    let actual = mock::client(&mock_server).list_channels().await?;
+    assert_eq!(5, actual.len());
+    assert!(actual[2].is_private);
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn resolve_channel_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = setup_mock_server().await?;
+    let client = mock::client(&mock_server);
+    assert_eq!("C0000000002", client.resolve_channel("#deploys").await?);
+    assert_eq!("G0000000003", client.resolve_channel("deploy-prod").await?);
+    assert_eq!("C9999999999", client.resolve_channel("C9999999999").await?);
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn resolve_channels_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = setup_mock_server().await?;
+    let actual = mock::client(&mock_server)
+      .resolve_channels(&["#general", "C9999999999", "deploys"])
+      .await?;
+    assert_eq!(vec!["C0000000001", "C9999999999", "C0000000002"], actual);
+    let requests = mock_server.received_requests().await.unwrap_or_default();
+    assert_eq!(2, requests.len(), "conversations.list is paged through once");
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn resolve_channel_not_found_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = setup_mock_server().await?;
+    match mock::client(&mock_server).resolve_channel("#deploy").await {
+      Err(SlackError::NotFound { name, close_matches, .. }) => {
+        assert_eq!("deploy", name);
+        assert_eq!(vec!["deploys", "deploy-prod"], close_matches);
+      }
+      other => panic!("expected not found error, got: {other:?}"),
+    }
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn resolve_channel_ambiguous_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = setup_mock_server().await?;
+    match mock::client(&mock_server).resolve_channel("dup").await {
+      Err(SlackError::Ambiguous { matches, .. }) => {
+        assert_eq!(vec!["C0000000004", "C0000000005"], matches);
+      }
+      other => panic!("expected ambiguous error, got: {other:?}"),
+    }
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn open_conversation_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    let body = r#"{"ok":true,"channel":{"id":"D0000000001"}}"#;
+    mock::given("conversations.open")
+      .and(body_json(serde_json::json!({ "users": "U0000000001" })))
+      .respond_with(mock::json(body))
+      .mount(&mock_server)
+      .await;
+    let users = [String::from("U0000000001")];
+    let actual = mock::client(&mock_server).open_conversation(&users).await?;
+    assert_eq!("D0000000001", actual);
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn open_group_conversation_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    let body = r#"{"ok":true,"channel":{"id":"G0000000001"}}"#;
+    mock::given("conversations.open")
+      .and(body_json(serde_json::json!({
+        "users": "U0000000001,U0000000002",
+      })))
+      .respond_with(mock::json(body))
+      .mount(&mock_server)
+      .await;
+    let users = [String::from("U0000000001"), String::from("U0000000002")];
+    let actual = mock::client(&mock_server).open_conversation(&users).await?;
+    assert_eq!("G0000000001", actual);
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn history_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    let route = "conversations.history";
+    let page1 = r#"{"ok":true,"messages":[{"type":"message","user":"U1","text":"three","ts":"1.3"},{"type":"message","subtype":"bot_message","bot_id":"B1","username":"deploy","text":"two","ts":"1.2"}],"has_more":true,"response_metadata":{"next_cursor":"page2"}}"#;
+    let page2 = r#"{"ok":true,"messages":[{"type":"message","user":"U2","text":"one","ts":"1.1"}],"has_more":true,"response_metadata":{"next_cursor":"page3"}}"#;
+    mock::given(route)
+      .and(body_string("channel=C1&inclusive=true&limit=3&oldest=1.0"))
+      .respond_with(mock::json(page1))
+      .expect(1)
+      .mount(&mock_server)
+      .await;
+    mock::given(route)
+      .and(body_string(
+        "channel=C1&cursor=page2&inclusive=true&limit=1&oldest=1.0",
+      ))
+      .respond_with(mock::json(page2))
+      .expect(1)
+      .mount(&mock_server)
+      .await;
+    let client = mock::client(&mock_server);
+    let range = Range {
+      oldest: Some(String::from("1.0")),
+      limit: Some(3),
+      ..Range::default()
+    };
+    let actual = client.history("C1", &range).await?;
+    let authors = actual.iter().map(Message::author).collect::<Vec<&str>>();
+    assert_eq!(vec!["U1", "deploy", "U2"], authors);
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn replies_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    let body = r#"{"ok":true,"messages":[{"type":"message","user":"U1","text":"deploy?","ts":"1.1","thread_ts":"1.1","reply_count":1},{"type":"message","user":"U2","text":"done","ts":"1.2","thread_ts":"1.1"}],"has_more":false}"#;
+    mock::given("conversations.replies")
+      .and(body_string("channel=C1&inclusive=true&limit=200&ts=1.1"))
+      .respond_with(mock::json(body))
+      .mount(&mock_server)
+      .await;
+    let client = mock::client(&mock_server);
+    let actual = client.replies("C1", "1.1", &Range::default()).await?;
+    let texts = actual.iter().map(|m| m.text.as_str()).collect::<Vec<&str>>();
+    assert_eq!(vec!["deploy?", "done"], texts);
+    assert_eq!(Some(1), actual[0].reply_count);
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn replies_paged_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    let route = "conversations.replies";
+    let page1 = r#"{"ok":true,"messages":[{"type":"message","user":"U1","text":"deploy?","ts":"1.1","thread_ts":"1.1","reply_count":2},{"type":"message","user":"U2","text":"started","ts":"1.2","thread_ts":"1.1"}],"has_more":true,"response_metadata":{"next_cursor":"page2"}}"#;
+    let page2 = r#"{"ok":true,"messages":[{"type":"message","user":"U1","text":"deploy?","ts":"1.1","thread_ts":"1.1","reply_count":2},{"type":"message","user":"U2","text":"done","ts":"1.3","thread_ts":"1.1"}],"has_more":false}"#;
+    mock::given(route)
+      .and(body_string("channel=C1&inclusive=true&limit=3&ts=1.1"))
+      .respond_with(mock::json(page1))
+      .expect(1)
+      .mount(&mock_server)
+      .await;
+    mock::given(route)
+      .and(body_string("channel=C1&cursor=page2&inclusive=true&limit=1&ts=1.1"))
+      .respond_with(mock::json(page2))
+      .expect(1)
+      .mount(&mock_server)
+      .await;
+    let client = mock::client(&mock_server);
+    let range = Range { limit: Some(3), ..Range::default() };
+    let actual = client.replies("C1", "1.1", &range).await?;
+    let texts = actual.iter().map(|m| m.text.as_str()).collect::<Vec<&str>>();
+    assert_eq!(vec!["deploy?", "started", "done"], texts);
+    Ok(())
+  }
+}
@@ -0,0 +1,296 @@
+use reqwest::StatusCode;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+/// Everything that can go wrong while talking to the slack api.
+#[derive(Debug)]
+pub enum SlackError {
+  /// The request could not be built, sent, or its body read.
+  Transport(reqwest::Error),
+  /// Slack answered with a non-success http status.
+  Status { status: StatusCode, body: String },
+  /// The response body was not the json we expected.
+  Decode(serde_json::Error),
+  /// The message was rejected before sending, ex) too many blocks.
+  Invalid(String),
+  /// Slack answered `"ok": false`, with any warnings that came along.
+  Api { error: ApiError, warning: Option<String>, messages: Vec<String> },
+  /// No `kind` (ex. channel) is named `name`.
+  NotFound { kind: &'static str, name: String, close_matches: Vec<String> },
+  /// More than one `kind` is named `name`, `matches` holds their ids.
+  Ambiguous { kind: &'static str, name: String, matches: Vec<String> },
+}
+
+impl Display for SlackError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    match self {
+      SlackError::Transport(e) => write!(f, "transport error: {e}"),
+      SlackError::Status { status, body } => {
+        write!(f, "unexpected http status {status}: {body}")
+      }
+      SlackError::Decode(e) => write!(f, "could not decode response: {e}"),
+      SlackError::Invalid(e) => write!(f, "invalid message: {e}"),
+      SlackError::Api { error, warning, messages } => {
+        write!(f, "slack api error: {error}")?;
+        if let Some(warning) = warning {
+          write!(f, " (warning: {warning})")?;
+        }
+        for message in messages {
+          write!(f, "\n  {message}")?;
+        }
+        Ok(())
+      }
+      SlackError::NotFound { kind, name, close_matches } => {
+        write!(f, "{kind} '{name}' not found")?;
+        if !close_matches.is_empty() {
+          write!(f, ", did you mean: {}", close_matches.join(", "))?;
+        }
+        Ok(())
+      }
+      SlackError::Ambiguous { kind, name, matches } => {
+        write!(f, "{kind} '{name}' is ambiguous: {}", matches.join(", "))
+      }
+    }
+  }
+}
+
+impl Error for SlackError {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    match self {
+      SlackError::Transport(e) => Some(e),
+      SlackError::Decode(e) => Some(e),
+      SlackError::Api { error, .. } => Some(error),
+      SlackError::Status { .. }
+      | SlackError::Invalid(_)
+      | SlackError::NotFound { .. }
+      | SlackError::Ambiguous { .. } => None,
+    }
+  }
+}
+
+impl From<reqwest::Error> for SlackError {
+  fn from(e: reqwest::Error) -> Self {
+    SlackError::Transport(e)
+  }
+}
+
+impl From<serde_json::Error> for SlackError {
+  fn from(e: serde_json::Error) -> Self {
+    SlackError::Decode(e)
+  }
+}
+
+impl From<ApiError> for SlackError {
+  fn from(e: ApiError) -> Self {
+    SlackError::Api { error: e, warning: None, messages: Vec::new() }
+  }
+}
+
+/// Error codes slack puts in the `error` field of a failed call.
+///
+/// Codes without a dedicated variant are kept verbatim in `Other`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiError {
+  AccountInactive,
+  AlreadyPinned,
+  AlreadyReacted,
+  CantDeleteMessage,
+  CantUpdateMessage,
+  ChannelNotFound,
+  EditWindowClosed,
+  InvalidAuth,
+  IsArchived,
+  MessageNotFound,
+  MissingScope,
+  NoPin,
+  NoReaction,
+  NotAuthed,
+  NotInChannel,
+  RateLimited,
+  TokenExpired,
+  TokenRevoked,
+  UserNotFound,
+  UsersNotFound,
+  Other(String),
+}
+
+impl ApiError {
+  pub fn from_code(code: &str) -> Self {
+    match code {
+      "account_inactive" => ApiError::AccountInactive,
+      "already_pinned" => ApiError::AlreadyPinned,
+      "already_reacted" => ApiError::AlreadyReacted,
+      "cant_delete_message" => ApiError::CantDeleteMessage,
+      "cant_update_message" => ApiError::CantUpdateMessage,
+      "channel_not_found" => ApiError::ChannelNotFound,
+      "edit_window_closed" => ApiError::EditWindowClosed,
+      "invalid_auth" => ApiError::InvalidAuth,
+      "is_archived" => ApiError::IsArchived,
+      "message_not_found" => ApiError::MessageNotFound,
+      "missing_scope" => ApiError::MissingScope,
+      "no_pin" => ApiError::NoPin,
+      "no_reaction" => ApiError::NoReaction,
+      "not_authed" => ApiError::NotAuthed,
+      "not_in_channel" => ApiError::NotInChannel,
+      "ratelimited" | "rate_limited" => ApiError::RateLimited,
+      "token_expired" => ApiError::TokenExpired,
+      "token_revoked" => ApiError::TokenRevoked,
+      "user_not_found" => ApiError::UserNotFound,
+      "users_not_found" => ApiError::UsersNotFound,
+      other => ApiError::Other(other.to_owned()),
+    }
+  }
+
+  /// The slack error code, ex) `channel_not_found`.
+  pub fn code(&self) -> &str {
+    match self {
+      ApiError::AccountInactive => "account_inactive",
+      ApiError::AlreadyPinned => "already_pinned",
+      ApiError::AlreadyReacted => "already_reacted",
+      ApiError::CantDeleteMessage => "cant_delete_message",
+      ApiError::CantUpdateMessage => "cant_update_message",
+      ApiError::ChannelNotFound => "channel_not_found",
+      ApiError::EditWindowClosed => "edit_window_closed",
+      ApiError::InvalidAuth => "invalid_auth",
+      ApiError::IsArchived => "is_archived",
+      ApiError::MessageNotFound => "message_not_found",
+      ApiError::MissingScope => "missing_scope",
+      ApiError::NoPin => "no_pin",
+      ApiError::NoReaction => "no_reaction",
+      ApiError::NotAuthed => "not_authed",
+      ApiError::NotInChannel => "not_in_channel",
+      ApiError::RateLimited => "ratelimited",
+      ApiError::TokenExpired => "token_expired",
+      ApiError::TokenRevoked => "token_revoked",
+      ApiError::UserNotFound => "user_not_found",
+      ApiError::UsersNotFound => "users_not_found",
+      ApiError::Other(code) => code,
+    }
+  }
+
+  /// The token is missing, invalid, or lacks permissions.
+  pub fn is_auth(&self) -> bool {
+    matches!(
+      self,
+      ApiError::AccountInactive
+        | ApiError::InvalidAuth
+        | ApiError::MissingScope
+        | ApiError::NotAuthed
+        | ApiError::TokenExpired
+        | ApiError::TokenRevoked
+    )
+  }
+
+  /// The channel doesn't exist or can't be posted to.
+  pub fn is_channel(&self) -> bool {
+    matches!(
+      self,
+      ApiError::ChannelNotFound | ApiError::IsArchived | ApiError::NotInChannel
+    )
+  }
+
+  /// The target message doesn't exist or can't be changed.
+  pub fn is_message(&self) -> bool {
+    matches!(
+      self,
+      ApiError::CantDeleteMessage
+        | ApiError::CantUpdateMessage
+        | ApiError::EditWindowClosed
+        | ApiError::MessageNotFound
+    )
+  }
+
+  /// No user matches the given id or email.
+  pub fn is_user(&self) -> bool {
+    matches!(self, ApiError::UserNotFound | ApiError::UsersNotFound)
+  }
+}
+
+impl Display for ApiError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+    f.write_str(self.code())
+  }
+}
+
+impl Error for ApiError {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn api_error_code_round_trip() {
+    let codes = [
+      "account_inactive",
+      "already_pinned",
+      "already_reacted",
+      "cant_delete_message",
+      "cant_update_message",
+      "channel_not_found",
+      "edit_window_closed",
+      "invalid_auth",
+      "is_archived",
+      "message_not_found",
+      "missing_scope",
+      "no_pin",
+      "no_reaction",
+      "not_authed",
+      "not_in_channel",
+      "ratelimited",
+      "token_expired",
+      "token_revoked",
+      "user_not_found",
+      "users_not_found",
+      "msg_too_long",
+    ];
+    for code in codes {
+      assert_eq!(code, ApiError::from_code(code).code());
+    }
+  }
+
+  #[test]
+  fn api_error_unknown_code() {
+    let actual = ApiError::from_code("msg_too_long");
+    assert_eq!(ApiError::Other(String::from("msg_too_long")), actual);
+    assert!(!actual.is_auth());
+    assert!(!actual.is_channel());
+  }
+
+  #[test]
+  fn display_api_error_with_warnings() {
+    let e = SlackError::Api {
+      error: ApiError::ChannelNotFound,
+      warning: Some(String::from("missing_charset")),
+      messages: vec![String::from("[WARN] A Content-Type header was not set")],
+    };
+    let expected = "slack api error: channel_not_found (warning: missing_charset)\n  [WARN] A Content-Type header was not set";
+    assert_eq!(expected, e.to_string());
+  }
+
+  #[test]
+  fn display_not_found() {
+    let e = SlackError::NotFound {
+      kind: "channel",
+      name: String::from("deploy"),
+      close_matches: vec![String::from("deploys"), String::from("deploy-prod")],
+    };
+    let expected =
+      "channel 'deploy' not found, did you mean: deploys, deploy-prod";
+    assert_eq!(expected, e.to_string());
+  }
+
+  #[test]
+  fn api_error_categories() {
+    assert!(ApiError::InvalidAuth.is_auth());
+    assert!(ApiError::MissingScope.is_auth());
+    assert!(!ApiError::InvalidAuth.is_channel());
+    assert!(ApiError::ChannelNotFound.is_channel());
+    assert!(ApiError::NotInChannel.is_channel());
+    assert!(!ApiError::NotInChannel.is_auth());
+    assert!(ApiError::MessageNotFound.is_message());
+    assert!(ApiError::CantDeleteMessage.is_message());
+    assert!(!ApiError::ChannelNotFound.is_message());
+    assert!(ApiError::UsersNotFound.is_user());
+    assert!(!ApiError::ChannelNotFound.is_user());
+  }
+}
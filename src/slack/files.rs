@@ -0,0 +1,229 @@
+//! File uploads, see <https://api.slack.com/messaging/files#upload>.
+
+use super::response::ResponseMetadata;
+use super::{Client, SlackError};
+use crate::globals::{
+  FILES_COMPLETE_UPLOAD_EXTERNAL, FILES_GET_UPLOAD_URL_EXTERNAL,
+};
+use reqwest::{header::CONTENT_TYPE, Client as HttpClient};
+use serde::{Deserialize, Serialize};
+
+/// A file to upload with [`Client::upload_file`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FileUpload {
+  pub content: Vec<u8>,
+  pub filename: String,
+  /// Shown instead of the filename when set.
+  pub title: Option<String>,
+}
+
+impl FileUpload {
+  pub fn new(filename: impl Into<String>, content: impl Into<Vec<u8>>) -> Self {
+    FileUpload {
+      content: content.into(),
+      filename: filename.into(),
+      title: None,
+    }
+  }
+
+  #[must_use]
+  pub fn title(mut self, title: impl Into<String>) -> Self {
+    self.title = Some(title.into());
+    self
+  }
+}
+
+/// Where uploaded files are shared, files without one stay private.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct Share {
+  pub channel_id: String,
+  /// Message posted along with the files.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub initial_comment: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub thread_ts: Option<String>,
+}
+
+/// A file as returned by `files.completeUploadExternal`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct File {
+  pub id: String,
+  #[serde(default)]
+  pub title: String,
+}
+
+/// Body of a `files.getUploadURLExternal` call.
+#[derive(Debug, Serialize)]
+struct GetUploadUrl<'a> {
+  filename: &'a str,
+  length: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct GetUploadUrlResponse {
+  file_id: String,
+  upload_url: String,
+}
+
+/// Body of a `files.completeUploadExternal` call.
+#[derive(Debug, Serialize)]
+struct CompleteUpload<'a> {
+  /// Json array of [`CompleteFile`]s.
+  files: String,
+  #[serde(flatten)]
+  share: Option<&'a Share>,
+}
+
+#[derive(Debug, Serialize)]
+struct CompleteFile<'a> {
+  id: &'a str,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  title: Option<&'a str>,
+}
+
+/// Body returned by a successful `files.completeUploadExternal`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UploadResponse {
+  pub ok: bool,
+  pub files: Vec<File>,
+  pub warning: Option<String>,
+  pub response_metadata: Option<ResponseMetadata>,
+}
+
+impl Client {
+  /// Upload one file, see [`Client::upload_files`].
+  pub async fn upload_file(
+    &self, file: FileUpload, share: Option<&Share>,
+  ) -> Result<UploadResponse, SlackError> {
+    self.upload_files(vec![file], share).await
+  }
+
+  /// Upload `files` and, with a `share`, post them together in one message.
+  ///
+  /// Each file gets its own upload url, the upload is only complete once
+  /// `files.completeUploadExternal` has been called for all of them.
+  pub async fn upload_files(
+    &self, files: Vec<FileUpload>, share: Option<&Share>,
+  ) -> Result<UploadResponse, SlackError> {
+    let mut ids = Vec::with_capacity(files.len());
+    let mut titles = Vec::with_capacity(files.len());
+    for file in files {
+      let get =
+        GetUploadUrl { filename: &file.filename, length: file.content.len() };
+      let res: GetUploadUrlResponse =
+        self.post_form(FILES_GET_UPLOAD_URL_EXTERNAL, &get).await?;
+      upload(&self.http, &res.upload_url, file.content).await?;
+      ids.push(res.file_id);
+      titles.push(file.title);
+    }
+    let files: Vec<_> = ids
+      .iter()
+      .zip(&titles)
+      .map(|(id, title)| CompleteFile { id, title: title.as_deref() })
+      .collect();
+    let complete =
+      CompleteUpload { files: serde_json::to_string(&files)?, share };
+    self.post_form(FILES_COMPLETE_UPLOAD_EXTERNAL, &complete).await
+  }
+}
+
+/// Send the raw bytes to an upload url, it answers in plain text.
+async fn upload(
+  http: &HttpClient, url: &str, content: Vec<u8>,
+) -> Result<(), SlackError> {
+  let res = http
+    .post(url)
+    .header(CONTENT_TYPE, "application/octet-stream")
+    .body(content)
+    .send()
+    .await?;
+  let status = res.status();
+  if !status.is_success() {
+    let body = res.text().await?;
+    return Err(SlackError::Status { status, body });
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::slack::mock;
+  use reqwest::StatusCode;
+  use std::error::Error;
+  use wiremock::matchers::{body_string, method, path};
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  async fn mount_upload_url(mock_server: &MockServer, id: &str, name: &str) {
+    let body = format!(
+      r#"{{"ok":true,"file_id":"{id}","upload_url":"{}/upload/{id}"}}"#,
+      mock_server.uri()
+    );
+    mock::given("files.getUploadURLExternal")
+      .and(body_string(format!("filename={name}&length=5")))
+      .respond_with(mock::json(body))
+      .mount(mock_server)
+      .await;
+  }
+
+  #[tokio::test]
+  async fn upload_files_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    mount_upload_url(&mock_server, "F1", "build.log").await;
+    mount_upload_url(&mock_server, "F2", "shot.png").await;
+    for id in ["F1", "F2"] {
+      Mock::given(method("POST"))
+        .and(path(format!("/upload/{id}")))
+        .and(body_string("hello"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("OK - 5"))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+    }
+    let expected_body = concat!(
+      "files=%5B%7B%22id%22%3A%22F1%22%7D%2C%7B%22id%22%3A%22F2%22%2C%22title%22%3A%22Screenshot%22%7D%5D",
+      "&channel_id=C1&initial_comment=build+failed&thread_ts=1734376519.228539",
+    );
+    let body = r#"{"ok":true,"files":[{"id":"F1","title":"build.log"},{"id":"F2","title":"Screenshot"}]}"#;
+    mock::given("files.completeUploadExternal")
+      .and(body_string(expected_body))
+      .respond_with(mock::json(body))
+      .mount(&mock_server)
+      .await;
+
+    let files = vec![
+      FileUpload::new("build.log", "hello"),
+      FileUpload::new("shot.png", "hello").title("Screenshot"),
+    ];
+    let share = Share {
+      channel_id: String::from("C1"),
+      initial_comment: Some(String::from("build failed")),
+      thread_ts: Some(String::from("1734376519.228539")),
+    };
+    let actual =
+      mock::client(&mock_server).upload_files(files, Some(&share)).await?;
+    assert_eq!(2, actual.files.len());
+    assert_eq!("Screenshot", actual.files[1].title);
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn upload_file_rejected_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    mount_upload_url(&mock_server, "F1", "build.log").await;
+    Mock::given(method("POST"))
+      .and(path("/upload/F1"))
+      .respond_with(ResponseTemplate::new(500).set_body_string("error"))
+      .mount(&mock_server)
+      .await;
+    let file = FileUpload::new("build.log", "hello");
+    match mock::client(&mock_server).upload_file(file, None).await {
+      Err(SlackError::Status { status, body }) => {
+        assert_eq!(StatusCode::INTERNAL_SERVER_ERROR, status);
+        assert_eq!("error", body);
+      }
+      other => panic!("expected status error, got: {other:?}"),
+    }
+    Ok(())
+  }
+}
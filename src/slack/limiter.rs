@@ -0,0 +1,133 @@
+//! Client side pacing to stay under slack's per method rate limits, see
+//! <https://api.slack.com/apis/rate-limits>.
+
+use crate::globals::{
+  CHAT_POST_EPHEMERAL, CHAT_POST_MESSAGE, CONVERSATIONS_LIST,
+  FILES_COMPLETE_UPLOAD_EXTERNAL, FILES_GET_UPLOAD_URL_EXTERNAL, PINS_ADD,
+  PINS_LIST, PINS_REMOVE, REACTIONS_REMOVE, USERS_LIST,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Slack's rate limit tiers, roughly calls per minute per workspace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tier {
+  /// 20+ per minute.
+  Two,
+  /// 50+ per minute.
+  Three,
+  /// 100+ per minute.
+  Four,
+  /// About 1 per second for each channel, `chat.postMessage`.
+  PerChannel,
+}
+
+impl Tier {
+  pub fn of(method: &str) -> Self {
+    match method {
+      CHAT_POST_MESSAGE => Tier::PerChannel,
+      CHAT_POST_EPHEMERAL
+      | FILES_COMPLETE_UPLOAD_EXTERNAL
+      | FILES_GET_UPLOAD_URL_EXTERNAL => Tier::Four,
+      CONVERSATIONS_LIST | PINS_ADD | PINS_LIST | PINS_REMOVE
+      | REACTIONS_REMOVE | USERS_LIST => Tier::Two,
+      _ => Tier::Three,
+    }
+  }
+
+  /// Time between calls once the burst is spent, and how many calls can go
+  /// out back to back.
+  fn rate(self) -> (Duration, u32) {
+    match self {
+      Tier::Two => (Duration::from_secs(3), 5),
+      Tier::Three => (Duration::from_millis(1_200), 10),
+      Tier::Four => (Duration::from_millis(600), 20),
+      Tier::PerChannel => (Duration::from_secs(1), 3),
+    }
+  }
+}
+
+/// Token buckets keyed by method, and channel for per channel methods.
+///
+/// Clones share their buckets, so a client cloned into concurrent tasks
+/// still paces all of them together.
+#[derive(Debug, Clone, Default)]
+pub struct Limiter {
+  /// When each bucket is next empty, see [`Limiter::reserve`].
+  next: Arc<Mutex<HashMap<(String, String), Instant>>>,
+}
+
+impl Limiter {
+  /// Wait until a call to `method` in `channel` fits within its tier.
+  pub async fn until_ready(&self, method: &str, channel: &str) {
+    let wait = self.reserve(method, channel, Instant::now());
+    if !wait.is_zero() {
+      tokio::time::sleep(wait).await;
+    }
+  }
+
+  /// Take a token from the bucket, returning how long until it is there.
+  fn reserve(&self, method: &str, channel: &str, now: Instant) -> Duration {
+    let tier = Tier::of(method);
+    let (interval, burst) = tier.rate();
+    let channel = if tier == Tier::PerChannel { channel } else { "" };
+    let mut next = self.next.lock().unwrap_or_else(PoisonError::into_inner);
+    let empty_at =
+      next.entry((method.to_owned(), channel.to_owned())).or_insert(now);
+    let start = (*empty_at).max(now);
+    *empty_at = start + interval;
+    start
+      .checked_sub(interval * (burst - 1))
+      .map_or(Duration::ZERO, |ready| ready.saturating_duration_since(now))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::globals::{CHAT_UPDATE, USERS_LOOKUP_BY_EMAIL};
+
+  #[test]
+  fn tiers() {
+    assert_eq!(Tier::PerChannel, Tier::of(CHAT_POST_MESSAGE));
+    assert_eq!(Tier::Two, Tier::of(USERS_LIST));
+    assert_eq!(Tier::Three, Tier::of(CHAT_UPDATE));
+    assert_eq!(Tier::Three, Tier::of(USERS_LOOKUP_BY_EMAIL));
+  }
+
+  #[test]
+  fn reserve_burst_then_pace() {
+    let limiter = Limiter::default();
+    let now = Instant::now();
+    let waits: Vec<_> = (0..5)
+      .map(|_| limiter.reserve(CHAT_POST_MESSAGE, "C1", now).as_secs())
+      .collect();
+    assert_eq!(vec![0, 0, 0, 1, 2], waits);
+    let later = now + Duration::from_secs(10);
+    assert!(limiter.reserve(CHAT_POST_MESSAGE, "C1", later).is_zero());
+  }
+
+  #[test]
+  fn reserve_per_channel() {
+    let limiter = Limiter::default();
+    let now = Instant::now();
+    for _ in 0..3 {
+      limiter.reserve(CHAT_POST_MESSAGE, "C1", now);
+    }
+    assert!(!limiter.reserve(CHAT_POST_MESSAGE, "C1", now).is_zero());
+    assert!(limiter.reserve(CHAT_POST_MESSAGE, "C2", now).is_zero());
+  }
+
+  #[test]
+  fn reserve_per_method() {
+    let limiter = Limiter::default();
+    let now = Instant::now();
+    for _ in 0..5 {
+      limiter.reserve(USERS_LIST, "C1", now);
+    }
+    // tiers other than chat.postMessage's are shared by every channel
+    assert!(!limiter.reserve(USERS_LIST, "C2", now).is_zero());
+    assert!(limiter.reserve(CHAT_UPDATE, "C1", now).is_zero());
+  }
+}
@@ -0,0 +1,65 @@
+//! Name matching shared by channel and user lookups.
+
+const MAX_CLOSE_MATCHES: usize = 5;
+
+/// Candidates within a few edits of `name`, or containing it, closest first.
+pub fn close_matches<'a>(
+  name: &str, candidates: impl IntoIterator<Item = &'a str>,
+) -> Vec<String> {
+  let max_distance = (name.chars().count() / 3).max(2);
+  let mut matches = candidates
+    .into_iter()
+    .filter_map(|candidate| {
+      let distance = edit_distance(name, candidate);
+      let close = distance <= max_distance || candidate.contains(name);
+      close.then_some((distance, candidate))
+    })
+    .collect::<Vec<(usize, &str)>>();
+  matches.sort_unstable();
+  matches.dedup_by_key(|(_, candidate)| *candidate);
+  matches
+    .into_iter()
+    .take(MAX_CLOSE_MATCHES)
+    .map(|(_, candidate)| candidate.to_owned())
+    .collect()
+}
+
+/// Levenshtein distance between `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+  let b = b.chars().collect::<Vec<char>>();
+  let mut prev = (0..=b.len()).collect::<Vec<usize>>();
+  for (i, ca) in a.chars().enumerate() {
+    let mut cur = vec![i + 1; b.len() + 1];
+    for (j, cb) in b.iter().enumerate() {
+      let cost = usize::from(ca != *cb);
+      cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+    }
+    prev = cur;
+  }
+  prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn edit_distances() {
+    assert_eq!(0, edit_distance("deploys", "deploys"));
+    assert_eq!(1, edit_distance("deploy", "deploys"));
+    assert_eq!(3, edit_distance("kitten", "sitting"));
+    assert_eq!(4, edit_distance("", "abcd"));
+  }
+
+  #[test]
+  fn close_matches_sorted() {
+    let candidates = ["general", "deploys", "deploy-prod", "random", "deploy"];
+    let actual = close_matches("deploy", candidates);
+    assert_eq!(vec!["deploy", "deploys", "deploy-prod"], actual);
+  }
+
+  #[test]
+  fn close_matches_none() {
+    assert!(close_matches("deploys", ["general", "random"]).is_empty());
+  }
+}
@@ -0,0 +1,27 @@
+//! Helpers for tests against a mock slack api.
+
+use super::Client;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockBuilder, MockServer, ResponseTemplate};
+
+/// A client calling `mock_server` instead of slack.
+pub fn client(mock_server: &MockServer) -> Client {
+  Client::new("test-token").api_url(format!("{}/api", mock_server.uri()))
+}
+
+/// Matches calls to the api `method`, ex) `chat.postMessage`.
+pub fn given(api_method: &str) -> MockBuilder {
+  Mock::given(method("POST")).and(path(format!("/api/{api_method}")))
+}
+
+/// A successful answer with `body` as json.
+pub fn json(body: impl Into<Vec<u8>>) -> ResponseTemplate {
+  ResponseTemplate::new(200).set_body_raw(body, "application/json")
+}
+
+/// Answer every call to the api `method` with `body` as json.
+pub async fn mount_json(
+  mock_server: &MockServer, api_method: &str, body: impl Into<Vec<u8>>,
+) {
+  given(api_method).respond_with(json(body)).mount(mock_server).await;
+}
@@ -1,61 +1,261 @@
+pub mod attachment;
+pub mod blocks;
+pub mod chat;
+pub mod conversations;
+mod error;
+pub mod files;
+mod limiter;
+mod lookup;
+#[cfg(test)]
+mod mock;
+pub mod pins;
+pub mod reactions;
 pub mod response;
+mod retry;
+pub mod users;
+pub mod webhook;
+
+pub use error::{ApiError, SlackError};
+pub use retry::Retry;
 
-use crate::globals::POST_MES_URL;
+use crate::globals::{CHAT_POST_MESSAGE, SLACK_API_URL};
+use attachment::Attachment;
+use blocks::Block;
+use limiter::Limiter;
 use reqwest::{
   header::{AUTHORIZATION, CONTENT_TYPE},
   Client as HttpClient, Request,
 };
 use response::Response;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::error::Error;
-
-#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy)]
-pub struct Message<'a> {
-  pub channel: &'a str,
-  pub icon_emoji: Option<&'a str>,
-  pub text: &'a str,
-  pub thread_ts: Option<&'a str>,
-  pub username: Option<&'a str>,
+use std::time::{Duration, Instant};
+
+/// Body of a `chat.postMessage` call.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Message {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub attachments: Option<Vec<Attachment>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub blocks: Option<Vec<Block>>,
+  /// Left empty when posting to a webhook, which has its own channel.
+  #[serde(skip_serializing_if = "String::is_empty")]
+  pub channel: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub icon_emoji: Option<String>,
+  /// Message body, or the notification fallback when `blocks` are set.
+  #[serde(skip_serializing_if = "String::is_empty")]
+  pub text: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub thread_ts: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub username: Option<String>,
+}
+
+impl Message {
+  pub fn new(channel: impl Into<String>, text: impl Into<String>) -> Self {
+    Message { channel: channel.into(), text: text.into(), ..Default::default() }
+  }
+
+  #[must_use]
+  pub fn attachments(mut self, attachments: Vec<Attachment>) -> Self {
+    self.attachments = Some(attachments);
+    self
+  }
+
+  #[must_use]
+  pub fn blocks(mut self, blocks: Vec<Block>) -> Self {
+    self.blocks = Some(blocks);
+    self
+  }
+
+  #[must_use]
+  pub fn icon_emoji(mut self, icon_emoji: impl Into<String>) -> Self {
+    self.icon_emoji = Some(icon_emoji.into());
+    self
+  }
+
+  /// Reply in the thread of the message with this timestamp.
+  #[must_use]
+  pub fn thread_ts(mut self, thread_ts: impl Into<String>) -> Self {
+    self.thread_ts = Some(thread_ts.into());
+    self
+  }
+
+  #[must_use]
+  pub fn username(mut self, username: impl Into<String>) -> Self {
+    self.username = Some(username.into());
+    self
+  }
+
+  /// Catch what slack would reject before spending a request on it.
+  pub fn validate(&self) -> Result<(), SlackError> {
+    if let Some(blocks) = &self.blocks {
+      blocks::validate(blocks).map_err(SlackError::Invalid)?;
+    }
+    let attachments = self.attachments.as_deref().unwrap_or_default();
+    if let Some(color) = attachments
+      .iter()
+      .filter_map(|a| a.color.as_deref())
+      .find(|c| !attachment::is_valid_color(c))
+    {
+      return Err(SlackError::Invalid(format!(
+        "attachment color '{color}' is not good, warning, danger or a hex color"
+      )));
+    }
+    if self.text.is_empty() && self.blocks.is_none() && attachments.is_empty() {
+      return Err(SlackError::Invalid(String::from(
+        "message has no text, blocks or attachments",
+      )));
+    }
+    Ok(())
+  }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, PartialOrd, Clone)]
-pub struct Client<'a> {
+/// Slack web api client authenticated with a bot/user OAuth token.
+///
+/// Clones share one connection pool, make one client and clone it rather
+/// than making a client per message.
+#[derive(Debug, Clone)]
+pub struct Client {
   bearer_token: String,
-  url: &'a str,
+  api_url: String,
+  http: HttpClient,
+  limiter: Limiter,
+  retry: Retry,
+}
+
+/// Whether waiting `wait` from now still ends before `deadline`, none being
+/// no deadline.
+fn before(deadline: Option<Instant>, wait: Duration) -> bool {
+  deadline.is_none_or(|deadline| {
+    Instant::now().checked_add(wait).is_some_and(|end| end < deadline)
+  })
+}
+
+/// The http client every call goes through: pooled keep-alive connections,
+/// http/2 where tls negotiates it (slack does), and timeouts so a hung
+/// connection can't stall a call forever.
+///
+/// Panics when the tls backend can't be set up, like `HttpClient::new` does,
+/// rather than quietly going without the timeouts.
+fn http_client() -> HttpClient {
+  HttpClient::builder()
+    .user_agent(concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION")))
+    .connect_timeout(Duration::from_secs(10))
+    .read_timeout(Duration::from_secs(30))
+    .pool_idle_timeout(Duration::from_secs(90))
+    .tcp_keepalive(Duration::from_secs(60))
+    .http2_keep_alive_interval(Duration::from_secs(30))
+    .build()
+    .expect("http client should build, is the tls backend available?")
 }
 
-impl Client<'_> {
+impl Client {
+  /// # Panics
+  ///
+  /// When the http client can't be built, ex) no usable tls backend.
   pub fn new(oauth_tok: &str) -> Self {
-    Client { bearer_token: format!("Bearer {oauth_tok}"), url: POST_MES_URL }
+    Client {
+      bearer_token: format!("Bearer {oauth_tok}"),
+      api_url: String::from(SLACK_API_URL),
+      http: http_client(),
+      limiter: Limiter::default(),
+      retry: Retry::default(),
+    }
   }
 
-  pub async fn send_message(
-    &self, message: &Message<'_>,
-  ) -> Result<Response, Box<dyn Error>> {
-    let request = self.build_request(message)?;
-    assert_eq!(
-      request.headers().get("authorization").unwrap(),
-      &self.bearer_token,
-      "Request authorization header does not match bearer token."
-    );
-    assert_eq!(
-      request.headers().get("content-type").unwrap(),
-      "application/json; charset=utf-8",
-      "Request header 'content-type' is not 'application/json; charset=utf-8'."
-    );
-    let response = HttpClient::new().execute(request).await?;
-    let res = Response::parse(response).await?;
-    Ok(res)
+  /// Call the api under `api_url` instead of slack's, ex) a slack compatible
+  /// server or a proxy. Every method is appended to it.
+  #[must_use]
+  pub fn api_url(mut self, api_url: impl Into<String>) -> Self {
+    self.api_url = api_url.into().trim_end_matches('/').to_owned();
+    self
+  }
+
+  /// Send every call through `http` instead of the default client, ex) to
+  /// set a proxy or other timeouts.
+  #[must_use]
+  pub fn http_client(mut self, http: HttpClient) -> Self {
+    self.http = http;
+    self
+  }
+
+  /// Retry rate limited and failed calls as `retry` says.
+  #[must_use]
+  pub fn retry(mut self, retry: Retry) -> Self {
+    self.retry = retry;
+    self
   }
 
-  fn build_request(
+  pub async fn send_message(
     &self, message: &Message,
-  ) -> Result<Request, Box<dyn Error>> {
-    let req = HttpClient::new()
-      .post(self.url)
+  ) -> Result<Response, SlackError> {
+    message.validate()?;
+    let request = self.build_request(CHAT_POST_MESSAGE, message)?;
+    self.execute(CHAT_POST_MESSAGE, &message.channel, request).await
+  }
+
+  /// POST `body` as json to the api `method`, ex) `chat.postMessage`.
+  async fn post<B: Serialize + ?Sized, T: DeserializeOwned>(
+    &self, method: &str, body: &B,
+  ) -> Result<T, SlackError> {
+    let request = self.build_request(method, body)?;
+    self.execute(method, "", request).await
+  }
+
+  /// POST `body` url encoded, read methods like `users.list` ignore json.
+  async fn post_form<B: Serialize + ?Sized, T: DeserializeOwned>(
+    &self, method: &str, body: &B,
+  ) -> Result<T, SlackError> {
+    let request = self
+      .http
+      .post(format!("{}/{method}", self.api_url))
+      .header(AUTHORIZATION, &self.bearer_token)
+      .form(body)
+      .build()?;
+    self.execute(method, "", request).await
+  }
+
+  /// Send `request` to `method`, retrying as `self.retry` allows, and decode
+  /// the answer.
+  ///
+  /// Every attempt, retries included, waits for its turn in the limiter.
+  /// A timeout too far out to be an `Instant` means no deadline at all.
+  async fn execute<T: DeserializeOwned>(
+    &self, method: &str, channel: &str, request: Request,
+  ) -> Result<T, SlackError> {
+    let deadline = Instant::now().checked_add(self.retry.timeout);
+    let mut attempt = 0;
+    loop {
+      // json and form bodies are plain bytes, so this always clones
+      self.limiter.until_ready(method, channel).await;
+      let Some(mut req) = request.try_clone() else {
+        let response = self.http.execute(request).await?;
+        return response::parse(response).await;
+      };
+      *req.timeout_mut() =
+        deadline.map(|d| d.saturating_duration_since(Instant::now()));
+      let outcome = self.http.execute(req).await;
+      match self.retry.delay(method, attempt, &outcome) {
+        Some(wait) if before(deadline, wait) => {
+          tokio::time::sleep(wait).await;
+          attempt += 1;
+        }
+        _ => return response::parse(outcome?).await,
+      }
+    }
+  }
+
+  fn build_request<B: Serialize + ?Sized>(
+    &self, method: &str, body: &B,
+  ) -> Result<Request, SlackError> {
+    let req = self
+      .http
+      .post(format!("{}/{method}", self.api_url))
       .header(AUTHORIZATION, &self.bearer_token)
       .header(CONTENT_TYPE, "application/json; charset=utf-8")
-      .json(&message)
+      .json(body)
       .build()?;
     Ok(req)
   }
@@ -64,22 +264,32 @@ impl Client<'_> {
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::globals::{ENV_SLACK_CHANNEL, ENV_SLACK_TOKEN};
+  use crate::globals::{ENV_SLACK_CHANNEL, ENV_SLACK_TOKEN, USERS_LIST};
   use core::str;
   use dotenvy::dotenv;
   use std::env;
-  use wiremock::matchers::{body_json, method, path};
-  use wiremock::{Mock, MockServer, ResponseTemplate};
+  use std::error::Error;
+  use wiremock::matchers::{body_json, header};
+  use wiremock::{MockServer, ResponseTemplate};
 
   #[test]
   fn new() -> Result<(), Box<dyn Error>> {
     let auth_tok = "testToken";
     let actual = Client::new(auth_tok);
-    let expected =
-      Client { url: POST_MES_URL, bearer_token: format!("Bearer {auth_tok}") };
+    assert_eq!(SLACK_API_URL, actual.api_url);
+    assert_eq!(format!("Bearer {auth_tok}"), actual.bearer_token);
+    assert_eq!(Retry::default(), actual.retry);
+    Ok(())
+  }
+
+  #[test]
+  fn api_url() -> Result<(), Box<dyn Error>> {
+    let client = Client::new("testToken").api_url("http://localhost:8065/api/");
+    assert_eq!("http://localhost:8065/api", client.api_url);
+    let req = client.build_request(CHAT_POST_MESSAGE, "{}")?;
     assert_eq!(
-      expected, actual,
-      "\n  expected: {expected:#?}\n  actual: {actual:#?}"
+      "http://localhost:8065/api/chat.postMessage",
+      req.url().as_str()
     );
     Ok(())
   }
@@ -87,14 +297,8 @@ mod tests {
   #[test]
   fn build_request_method() -> Result<(), Box<dyn Error>> {
     let client = Client::new("testToken");
-    let msg = Message {
-      channel: "testChannel",
-      icon_emoji: None,
-      text: "testMessageText",
-      thread_ts: None,
-      username: None,
-    };
-    let actual = client.build_request(&msg)?;
+    let msg = Message::new("testChannel", "testMessageText");
+    let actual = client.build_request(CHAT_POST_MESSAGE, &msg)?;
     assert_eq!(reqwest::Method::POST, actual.method());
     Ok(())
   }
@@ -103,14 +307,8 @@ mod tests {
   fn build_request_headers() -> Result<(), Box<dyn Error>> {
     let tok = "testToken";
     let client = Client::new(tok);
-    let msg = Message {
-      channel: "testChannel",
-      icon_emoji: None,
-      text: "testMessageText",
-      thread_ts: None,
-      username: None,
-    };
-    let req = client.build_request(&msg)?;
+    let msg = Message::new("testChannel", "testMessageText");
+    let req = client.build_request(CHAT_POST_MESSAGE, &msg)?;
     let headers = req.headers();
     let auth_header = "authorization";
     let content_header = "content-type";
@@ -131,14 +329,11 @@ mod tests {
   fn build_request_body() -> Result<(), Box<dyn Error>> {
     let client = Client::new("testToken");
     let ts = "1734376519.228539";
-    let msg = Message {
-      channel: "testChannel",
-      icon_emoji: Some(":test:"),
-      text: "testMessageText",
-      thread_ts: Some(ts),
-      username: Some("testName"),
-    };
-    let req = client.build_request(&msg)?;
+    let msg = Message::new("testChannel", "testMessageText")
+      .icon_emoji(":test:")
+      .thread_ts(ts)
+      .username("testName");
+    let req = client.build_request(CHAT_POST_MESSAGE, &msg)?;
     let body = req.body().unwrap().as_bytes().unwrap();
     let actual = str::from_utf8(body)?;
     let expected = r#"{"channel":"testChannel","icon_emoji":":test:","text":"testMessageText","thread_ts":"1734376519.228539","username":"testName"}"#;
@@ -150,23 +345,287 @@ mod tests {
   async fn send_message_mock() -> Result<(), Box<dyn Error>> {
     let ts = "1734376519.228539";
     let channel = "test-channel";
-    let icon_emoji = Some(":test:");
     let text = "testMessageText";
-    let username = Some("TEST-USERNAME");
-    let route = "/api/chat.postMessage";
-    let msg =
-      Message { channel, icon_emoji, text, thread_ts: Some(ts), username };
+    let username = "TEST-USERNAME";
+    let route = "chat.postMessage";
+    let msg = Message::new(channel, text)
+      .icon_emoji(":test:")
+      .thread_ts(ts)
+      .username(username);
     let mock_server = setup_mock_server(&msg, route, ts).await?;
-    let client = Client {
-      bearer_token: String::from("test-token"),
-      url: &format!("{}{route}", mock_server.uri()),
-    };
+    let client = mock::client(&mock_server);
     let actual = client.send_message(&msg).await?;
     assert!(actual.ok);
     assert_eq!(channel, actual.channel);
     assert_eq!(text, actual.message.text);
     assert_eq!(ts, actual.ts);
-    assert_eq!(username.unwrap(), actual.message.username);
+    assert_eq!(Some(username), actual.message.username.as_deref());
+    Ok(())
+  }
+
+  #[test]
+  fn build_request_body_blocks() -> Result<(), Box<dyn Error>> {
+    let client = Client::new("testToken");
+    let msg = Message::new("testChannel", "")
+      .blocks(vec![Block::header("Release"), Block::divider()]);
+    let req = client.build_request(CHAT_POST_MESSAGE, &msg)?;
+    let body = req.body().unwrap().as_bytes().unwrap();
+    let actual = str::from_utf8(body)?;
+    let expected = r#"{"blocks":[{"type":"header","text":{"type":"plain_text","text":"Release"}},{"type":"divider"}],"channel":"testChannel"}"#;
+    assert_eq!(expected, actual, "\nexpected: {expected}\nactual:{actual}");
+    Ok(())
+  }
+
+  #[test]
+  fn validate_message() {
+    assert!(Message::new("c", "text").validate().is_ok());
+    assert!(matches!(
+      Message::new("c", "").validate(),
+      Err(SlackError::Invalid(_))
+    ));
+    let blocks = vec![Block::divider(); blocks::MAX_BLOCKS + 1];
+    assert!(matches!(
+      Message::new("c", "text").blocks(blocks).validate(),
+      Err(SlackError::Invalid(_))
+    ));
+  }
+
+  #[test]
+  fn build_request_body_attachments() -> Result<(), Box<dyn Error>> {
+    let client = Client::new("testToken");
+    let attachment = Attachment::new()
+      .color("good")
+      .field(attachment::Field::new("Build", "1234"));
+    let msg =
+      Message::new("testChannel", "deployed").attachments(vec![attachment]);
+    let req = client.build_request(CHAT_POST_MESSAGE, &msg)?;
+    let body = req.body().unwrap().as_bytes().unwrap();
+    let actual = str::from_utf8(body)?;
+    let expected = r#"{"attachments":[{"color":"good","fields":[{"title":"Build","value":"1234","short":true}]}],"channel":"testChannel","text":"deployed"}"#;
+    assert_eq!(expected, actual, "\nexpected: {expected}\nactual:{actual}");
+    Ok(())
+  }
+
+  #[test]
+  fn validate_attachment_color() {
+    let msg = |color| {
+      Message::new("c", "text")
+        .attachments(vec![Attachment::new().color(color)])
+    };
+    assert!(msg("danger").validate().is_ok());
+    assert!(matches!(msg("red").validate(), Err(SlackError::Invalid(_))));
+    let no_text = Message::new("c", "").attachments(vec![Attachment::new()]);
+    assert!(no_text.validate().is_ok());
+  }
+
+  #[tokio::test]
+  async fn send_message_status_mock() -> Result<(), Box<dyn Error>> {
+    let route = "chat.postMessage";
+    let mock_server = MockServer::start().await;
+    // the message may be posted already, retrying could post it twice
+    mock::given(route)
+      .respond_with(ResponseTemplate::new(500).set_body_string("oops"))
+      .expect(1)
+      .mount(&mock_server)
+      .await;
+    let client = mock::client(&mock_server).retry(fast_retry());
+    let msg = Message::new("test-channel", "testMessageText");
+    match client.send_message(&msg).await {
+      Err(SlackError::Status { status, body }) => {
+        assert_eq!(500, status.as_u16());
+        assert_eq!("oops", body);
+      }
+      other => panic!("expected status error, got: {other:?}"),
+    }
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn delete_message_status_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    mock::given("chat.delete")
+      .respond_with(ResponseTemplate::new(502).set_body_string("oops"))
+      .expect(4)
+      .mount(&mock_server)
+      .await;
+    let client = mock::client(&mock_server).retry(fast_retry());
+    match client.delete_message("C1", "1.2").await {
+      Err(SlackError::Status { status, .. }) => {
+        assert_eq!(502, status.as_u16());
+      }
+      other => panic!("expected status error, got: {other:?}"),
+    }
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn retry_waits_for_limiter_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    mock::given("users.list")
+      .respond_with(ResponseTemplate::new(503))
+      .up_to_n_times(1)
+      .expect(1)
+      .mount(&mock_server)
+      .await;
+    mock::given("users.list")
+      .respond_with(mock::json(r#"{"ok":true,"members":[]}"#))
+      .expect(1)
+      .mount(&mock_server)
+      .await;
+    let client = mock::client(&mock_server).retry(fast_retry());
+    // leave one call of users.list's burst of 5, the retry has to wait
+    for _ in 0..4 {
+      client.limiter.until_ready(USERS_LIST, "").await;
+    }
+    let start = Instant::now();
+    client.list_users().await?;
+    assert!(start.elapsed() >= Duration::from_secs(2), "{:?}", start.elapsed());
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn send_message_api_error_mock() -> Result<(), Box<dyn Error>> {
+    let route = "chat.postMessage";
+    let mock_server = MockServer::start().await;
+    let body = r#"{"ok":false,"error":"not_in_channel"}"#;
+    mock::given(route)
+      .respond_with(mock::json(body))
+      .expect(1)
+      .mount(&mock_server)
+      .await;
+    let client = mock::client(&mock_server).retry(fast_retry());
+    let msg = Message::new("test-channel", "testMessageText");
+    match client.send_message(&msg).await {
+      Err(SlackError::Api { error, .. }) => {
+        assert_eq!(ApiError::NotInChannel, error);
+      }
+      other => panic!("expected api error, got: {other:?}"),
+    }
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn send_message_user_agent_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    let user_agent = format!("slack-message/{}", env!("CARGO_PKG_VERSION"));
+    let body = r#"{"ok":true,"channel":"C1","ts":"1.2","message":{"type":"message","text":"hi","ts":"1.2","app_id":"A1","bot_id":"B1","username":"bot"}}"#;
+    mock::given("chat.postMessage")
+      .and(header("user-agent", user_agent.as_str()))
+      .respond_with(mock::json(body))
+      .expect(2)
+      .mount(&mock_server)
+      .await;
+    let client = mock::client(&mock_server);
+    // clones go through the same client, and so the same connections
+    let clone = client.clone();
+    client.send_message(&Message::new("C1", "hi")).await?;
+    clone.send_message(&Message::new("C1", "hi")).await?;
+    Ok(())
+  }
+
+  fn fast_retry() -> Retry {
+    Retry { base_delay: Duration::from_millis(1), ..Retry::default() }
+  }
+
+  #[tokio::test]
+  async fn send_message_rate_limited_mock() -> Result<(), Box<dyn Error>> {
+    let route = "chat.postMessage";
+    let mock_server = MockServer::start().await;
+    mock::given(route)
+      .respond_with(
+        ResponseTemplate::new(429).insert_header("Retry-After", "0"),
+      )
+      .up_to_n_times(2)
+      .expect(2)
+      .mount(&mock_server)
+      .await;
+    let body = r#"{"ok":true,"channel":"C1","ts":"1734376519.228539","message":{"type":"message","text":"hi","ts":"1734376519.228539","app_id":"A1","bot_id":"B1","username":"bot"}}"#;
+    mock::given(route)
+      .respond_with(mock::json(body))
+      .expect(1)
+      .mount(&mock_server)
+      .await;
+    let client = mock::client(&mock_server).retry(fast_retry());
+    let msg = Message::new("C1", "hi");
+    let actual = client.send_message(&msg).await?;
+    assert_eq!("1734376519.228539", actual.ts);
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn send_message_retries_exhausted_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    mock::given("chat.postMessage")
+      .respond_with(
+        ResponseTemplate::new(429).insert_header("Retry-After", "0"),
+      )
+      .expect(2)
+      .mount(&mock_server)
+      .await;
+    let client = mock::client(&mock_server)
+      .retry(Retry { max_retries: 1, ..fast_retry() });
+    let msg = Message::new("test-channel", "testMessageText");
+    match client.send_message(&msg).await {
+      Err(SlackError::Status { status, .. }) => {
+        assert_eq!(429, status.as_u16());
+      }
+      other => panic!("expected status error, got: {other:?}"),
+    }
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn send_message_timeout_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    mock::given("chat.postMessage")
+      .respond_with(
+        ResponseTemplate::new(429).insert_header("Retry-After", "60"),
+      )
+      .expect(1)
+      .mount(&mock_server)
+      .await;
+    let client = mock::client(&mock_server)
+      .retry(Retry { timeout: Duration::from_secs(5), ..fast_retry() });
+    let msg = Message::new("test-channel", "testMessageText");
+    let actual = client.send_message(&msg).await;
+    assert!(matches!(actual, Err(SlackError::Status { .. })), "{actual:?}");
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn retry_after_too_long_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    mock::given("chat.postMessage")
+      .respond_with(
+        ResponseTemplate::new(429)
+          .insert_header("Retry-After", "18446744073709551615"),
+      )
+      .expect(1)
+      .mount(&mock_server)
+      .await;
+    let client = mock::client(&mock_server)
+      .retry(Retry { timeout: Duration::from_secs(5), ..fast_retry() });
+    let msg = Message::new("test-channel", "testMessageText");
+    let actual = client.send_message(&msg).await;
+    assert!(matches!(actual, Err(SlackError::Status { .. })), "{actual:?}");
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn unbounded_timeout_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    mock::given("chat.delete")
+      .respond_with(ResponseTemplate::new(502))
+      .expect(2)
+      .mount(&mock_server)
+      .await;
+    let client = mock::client(&mock_server).retry(Retry {
+      max_retries: 1,
+      timeout: Duration::MAX,
+      ..fast_retry()
+    });
+    let actual = client.delete_message("C1", "1.2").await;
+    assert!(matches!(actual, Err(SlackError::Status { .. })), "{actual:?}");
     Ok(())
   }
 
@@ -175,40 +634,38 @@ mod tests {
   async fn send_message() -> Result<(), Box<dyn Error>> {
     dotenv().ok();
     let tok = env::var(ENV_SLACK_TOKEN)?;
-    let channel = &env::var(ENV_SLACK_CHANNEL)?;
+    let channel = env::var(ENV_SLACK_CHANNEL)?;
     let slack = Client::new(&tok);
     let text = "testMessageText";
-    let thread_ts = None;
-    let username = Some("TEST-NAME");
-    let icon_emoji = Some(":test:");
-    let msg = Message { channel, icon_emoji, text, thread_ts, username };
+    let username = "TEST-NAME";
+    let icon_emoji = ":test:";
+    let msg =
+      Message::new(&channel, text).icon_emoji(icon_emoji).username(username);
     let actual = slack.send_message(&msg).await?;
     println!("actual:\n{actual:?}");
     assert!(actual.ok);
-    assert!(actual.error.is_none());
-    assert_eq!(channel, &actual.channel);
-    assert_eq!(text, &actual.message.text);
-    assert_eq!(username.unwrap(), &actual.message.username);
-    assert_eq!(icon_emoji.unwrap(), &actual.message.icons.unwrap().emoji);
+    assert!(actual.warning.is_none());
+    assert_eq!(channel, actual.channel);
+    assert_eq!(text, actual.message.text);
+    assert_eq!(Some(username), actual.message.username.as_deref());
+    assert_eq!(icon_emoji, actual.message.icons.unwrap().emoji);
     Ok(())
   }
 
   async fn setup_mock_server(
-    msg: &Message<'_>, route: &str, ts: &str,
+    msg: &Message, route: &str, ts: &str,
   ) -> Result<MockServer, Box<dyn Error>> {
     let mock_server = MockServer::start().await;
     let mock_body = format!(
       r#"{{"ok":true,"channel":"{channel}","ts":"{ts}","message":{{"subtype":"bot_message","text":"{text}","username":"{username}","icons":{{"emoji":"{icon}"}},"type":"message","ts":"{ts}","bot_id":"B12345ABCDE","app_id":"A12345ABCDE","blocks":[{{"type":"rich_text","block_id":"Cy=M","elements":[{{"type":"rich_text_section","elements":[{{"type":"text","text":"{text}"}}]}}]}}]}}}}"#,
       channel = msg.channel,
       text = msg.text,
-      username = msg.username.unwrap(),
-      icon = msg.icon_emoji.unwrap()
+      username = msg.username.as_deref().unwrap(),
+      icon = msg.icon_emoji.as_deref().unwrap()
     );
-    let template =
-      ResponseTemplate::new(200).set_body_raw(mock_body, "application/json");
-    Mock::given(method("POST"))
-      .and(path(route))
-      .and(body_json(&msg))
+    let template = mock::json(mock_body);
+    mock::given(route)
+      .and(body_json(msg))
       .respond_with(template)
       .mount(&mock_server)
       .await;
@@ -0,0 +1,131 @@
+//! Pinned messages, see <https://api.slack.com/methods/pins.add>.
+
+use super::response::OkResponse;
+use super::{Client, SlackError};
+use crate::globals::{PINS_ADD, PINS_LIST, PINS_REMOVE};
+use serde::{Deserialize, Serialize};
+
+/// Something pinned to a channel, as listed by `pins.list`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pin {
+  /// `message`, or `file` for files pinned the legacy way.
+  #[serde(rename = "type")]
+  pub kind: String,
+  #[serde(default)]
+  pub created: i64,
+  #[serde(default)]
+  pub created_by: String,
+  /// Left out for anything that isn't a message.
+  pub message: Option<PinnedMessage>,
+}
+
+/// The parts of a pinned message `pins.list` returns.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PinnedMessage {
+  pub ts: String,
+  #[serde(default)]
+  pub text: String,
+  #[serde(default)]
+  pub permalink: String,
+}
+
+/// Body of a `pins.add` or `pins.remove` call.
+#[derive(Debug, Serialize)]
+struct PinMessage<'a> {
+  channel: &'a str,
+  timestamp: &'a str,
+}
+
+/// Body of a `pins.list` call.
+#[derive(Debug, Serialize)]
+struct List<'a> {
+  channel: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListResponse {
+  #[serde(default)]
+  items: Vec<Pin>,
+}
+
+impl Client {
+  /// Pin the message at `ts` to `channel`.
+  pub async fn pin(
+    &self, channel: &str, ts: &str,
+  ) -> Result<OkResponse, SlackError> {
+    self.post(PINS_ADD, &PinMessage { channel, timestamp: ts }).await
+  }
+
+  /// Unpin the message at `ts` from `channel`.
+  pub async fn unpin(
+    &self, channel: &str, ts: &str,
+  ) -> Result<OkResponse, SlackError> {
+    self.post(PINS_REMOVE, &PinMessage { channel, timestamp: ts }).await
+  }
+
+  /// Everything pinned to `channel`.
+  pub async fn list_pins(&self, channel: &str) -> Result<Vec<Pin>, SlackError> {
+    let res: ListResponse =
+      self.post_form(PINS_LIST, &List { channel }).await?;
+    Ok(res.items)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::slack::mock;
+  use crate::slack::ApiError;
+  use std::error::Error;
+  use wiremock::matchers::{body_json, body_string};
+  use wiremock::MockServer;
+
+  #[tokio::test]
+  async fn pin_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    let expected_body =
+      serde_json::json!({ "channel": "C1", "timestamp": "1734376519.228539" });
+    mock::given("pins.add")
+      .and(body_json(expected_body))
+      .respond_with(mock::json(r#"{"ok":true}"#))
+      .mount(&mock_server)
+      .await;
+    let client = mock::client(&mock_server);
+    let actual = client.pin("C1", "1734376519.228539").await?;
+    assert!(actual.ok);
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn unpin_no_pin_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    let body = r#"{"ok":false,"error":"no_pin"}"#;
+    mock::mount_json(&mock_server, "pins.remove", body).await;
+    let client = mock::client(&mock_server);
+    match client.unpin("C1", "1.2").await {
+      Err(SlackError::Api { error, .. }) => assert_eq!(ApiError::NoPin, error),
+      other => panic!("expected api error, got: {other:?}"),
+    }
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn list_pins_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    let body = r#"{"ok":true,"items":[{"type":"message","created":1734376600,"created_by":"U1","channel":"C1","message":{"type":"message","text":"incident summary","ts":"1734376519.228539","permalink":"https://example.slack.com/archives/C1/p1734376519228539"}},{"type":"file","created":1734376700,"created_by":"U1"}]}"#;
+    mock::given("pins.list")
+      .and(body_string("channel=C1"))
+      .respond_with(mock::json(body))
+      .mount(&mock_server)
+      .await;
+    let client = mock::client(&mock_server);
+    let actual = client.list_pins("C1").await?;
+    assert_eq!(2, actual.len());
+    let message = actual[0].message.as_ref().unwrap();
+    assert_eq!("1734376519.228539", message.ts);
+    assert_eq!("incident summary", message.text);
+    assert_eq!("file", actual[1].kind);
+    assert!(actual[1].message.is_none());
+    Ok(())
+  }
+}
@@ -0,0 +1,154 @@
+//! Emoji reactions on messages, see
+//! <https://api.slack.com/methods/reactions.add>.
+
+use super::response::OkResponse;
+use super::{Client, SlackError};
+use crate::globals::{REACTIONS_ADD, REACTIONS_GET, REACTIONS_REMOVE};
+use serde::{Deserialize, Serialize};
+
+/// An emoji on a message and who added it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reaction {
+  pub name: String,
+  pub count: u32,
+  #[serde(default)]
+  pub users: Vec<String>,
+}
+
+/// Body of a `reactions.add` or `reactions.remove` call.
+#[derive(Debug, Serialize)]
+struct React<'a> {
+  channel: &'a str,
+  name: &'a str,
+  timestamp: &'a str,
+}
+
+/// Body of a `reactions.get` call.
+#[derive(Debug, Serialize)]
+struct Get<'a> {
+  channel: &'a str,
+  /// Without it slack cuts the list of users short.
+  full: bool,
+  timestamp: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct GetResponse {
+  message: Reacted,
+}
+
+#[derive(Debug, Deserialize)]
+struct Reacted {
+  /// Left out when the message has no reactions.
+  #[serde(default)]
+  reactions: Vec<Reaction>,
+}
+
+/// The emoji's name the way slack wants it for reactions, ex) ':tada:' and
+/// 'tada' are both 'tada'.
+///
+/// Skin tones stay attached, ':+1::skin-tone-2:' is '+1::skin-tone-2'.
+pub fn emoji_name(emoji: &str) -> &str {
+  emoji.trim().trim_matches(':')
+}
+
+impl Client {
+  /// React to the message at `ts` in `channel` with `emoji`, colons or not.
+  pub async fn add_reaction(
+    &self, channel: &str, ts: &str, emoji: &str,
+  ) -> Result<OkResponse, SlackError> {
+    let react = React { channel, name: emoji_name(emoji), timestamp: ts };
+    self.post(REACTIONS_ADD, &react).await
+  }
+
+  /// Take back a reaction added with [`Client::add_reaction`].
+  pub async fn remove_reaction(
+    &self, channel: &str, ts: &str, emoji: &str,
+  ) -> Result<OkResponse, SlackError> {
+    let react = React { channel, name: emoji_name(emoji), timestamp: ts };
+    self.post(REACTIONS_REMOVE, &react).await
+  }
+
+  /// Every reaction on the message at `ts` in `channel`.
+  pub async fn get_reactions(
+    &self, channel: &str, ts: &str,
+  ) -> Result<Vec<Reaction>, SlackError> {
+    let get = Get { channel, full: true, timestamp: ts };
+    let res: GetResponse = self.post_form(REACTIONS_GET, &get).await?;
+    Ok(res.message.reactions)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::slack::mock;
+  use crate::slack::ApiError;
+  use std::error::Error;
+  use wiremock::matchers::{body_json, body_string};
+  use wiremock::MockServer;
+
+  #[test]
+  fn emoji_names() {
+    assert_eq!("tada", emoji_name(":tada:"));
+    assert_eq!("tada", emoji_name("tada"));
+    assert_eq!("white_check_mark", emoji_name(" :white_check_mark: "));
+    assert_eq!("+1::skin-tone-2", emoji_name(":+1::skin-tone-2:"));
+  }
+
+  #[tokio::test]
+  async fn add_reaction_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    let expected_body = serde_json::json!({
+      "channel": "C1",
+      "name": "white_check_mark",
+      "timestamp": "1734376519.228539",
+    });
+    mock::given("reactions.add")
+      .and(body_json(expected_body))
+      .respond_with(mock::json(r#"{"ok":true}"#))
+      .mount(&mock_server)
+      .await;
+    let client = mock::client(&mock_server);
+    let actual = client
+      .add_reaction("C1", "1734376519.228539", ":white_check_mark:")
+      .await?;
+    assert!(actual.ok);
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn remove_reaction_no_reaction_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    let body = r#"{"ok":false,"error":"no_reaction"}"#;
+    mock::mount_json(&mock_server, "reactions.remove", body).await;
+    let client = mock::client(&mock_server);
+    match client.remove_reaction("C1", "1.2", "x").await {
+      Err(SlackError::Api { error, .. }) => {
+        assert_eq!(ApiError::NoReaction, error);
+      }
+      other => panic!("expected api error, got: {other:?}"),
+    }
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn get_reactions_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    let body = r#"{"ok":true,"type":"message","channel":"C1","message":{"type":"message","text":"deployed","ts":"1734376519.228539","reactions":[{"name":"white_check_mark","count":2,"users":["U1","U2"]}]}}"#;
+    mock::given("reactions.get")
+      .and(body_string("channel=C1&full=true&timestamp=1734376519.228539"))
+      .respond_with(mock::json(body))
+      .mount(&mock_server)
+      .await;
+    let client = mock::client(&mock_server);
+    let actual = client.get_reactions("C1", "1734376519.228539").await?;
+    let expected = vec![Reaction {
+      name: String::from("white_check_mark"),
+      count: 2,
+      users: vec![String::from("U1"), String::from("U2")],
+    }];
+    assert_eq!(expected, actual);
+    Ok(())
+  }
+}
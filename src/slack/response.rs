@@ -1,41 +1,214 @@
+use super::{ApiError, SlackError};
 use reqwest::Response as HttpResponse;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::error::Error;
 
+/// Body returned by a successful `chat.postMessage`.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Response {
   pub ok: bool,
   pub channel: String,
   pub ts: String,
   pub message: Message,
-  pub error: Option<String>,
+  pub warning: Option<String>,
+  pub response_metadata: Option<ResponseMetadata>,
 }
 
 impl Response {
   pub fn parse_str(s: &str) -> Result<Self, serde_json::Error> {
     serde_json::from_str(s)
   }
-  pub async fn parse(res: HttpResponse) -> Result<Self, Box<dyn Error>> {
-    let body = res.text().await?;
-    Ok(Self::parse_str(&body)?)
+  /// Fails on a non-success http status or when slack answers `"ok": false`.
+  pub async fn parse(res: HttpResponse) -> Result<Self, SlackError> {
+    parse(res).await
   }
 }
 
+/// Body returned by any failed call, ex) `{"ok":false,"error":"not_authed"}`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorResponse {
+  pub ok: bool,
+  #[serde(default = "unknown_error")]
+  pub error: String,
+  pub warning: Option<String>,
+  pub response_metadata: Option<ResponseMetadata>,
+}
+
+impl From<ErrorResponse> for SlackError {
+  fn from(res: ErrorResponse) -> Self {
+    SlackError::Api {
+      error: ApiError::from_code(&res.error),
+      warning: res.warning,
+      messages: res.response_metadata.map(|m| m.messages).unwrap_or_default(),
+    }
+  }
+}
+
+fn unknown_error() -> String {
+  String::from("unknown_error")
+}
+
+/// Extra details slack attaches to both successful and failed calls.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ResponseMetadata {
+  #[serde(default)]
+  pub messages: Vec<String>,
+  #[serde(default)]
+  pub warnings: Vec<String>,
+  /// Set when a paginated call has more results, empty on the last page.
+  pub next_cursor: Option<String>,
+}
+
+impl ResponseMetadata {
+  pub(crate) fn next_cursor(meta: Option<&Self>) -> Option<String> {
+    meta.and_then(|m| m.next_cursor.clone()).filter(|c| !c.is_empty())
+  }
+}
+
+/// Body returned by calls that answer with nothing but `"ok": true`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OkResponse {
+  pub ok: bool,
+  pub warning: Option<String>,
+  pub response_metadata: Option<ResponseMetadata>,
+}
+
+/// Decode a successful body as `T`, or a failed one as an api error.
+pub(crate) fn parse_body<T: DeserializeOwned>(
+  body: &str,
+) -> Result<T, SlackError> {
+  let OkResponse { ok, .. } = serde_json::from_str(body)?;
+  if ok {
+    Ok(serde_json::from_str(body)?)
+  } else {
+    let res: ErrorResponse = serde_json::from_str(body)?;
+    Err(res.into())
+  }
+}
+
+/// Fails on a non-success http status or when slack answers `"ok": false`.
+pub(crate) async fn parse<T: DeserializeOwned>(
+  res: HttpResponse,
+) -> Result<T, SlackError> {
+  let status = res.status();
+  let body = res.text().await?;
+  if !status.is_success() {
+    return Err(SlackError::Status { status, body });
+  }
+  parse_body(&body)
+}
+
+/// A message as slack returns it, posted by an app or bot, or read back with
+/// `conversations.history`.
+///
+/// Only bots have `app_id`, `bot_id` and `username`, people have `user`.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Message {
   #[serde(rename = "type")]
   pub _type: String,
-  pub app_id: String,
-  pub bot_id: String,
+  pub subtype: Option<String>,
+  pub app_id: Option<String>,
+  pub bot_id: Option<String>,
   pub team: Option<String>,
+  #[serde(default)]
   pub text: String,
   pub ts: String,
+  /// Set on every message of a thread, the parent's ts.
+  pub thread_ts: Option<String>,
+  pub reply_count: Option<u32>,
   pub user: Option<String>,
-  pub username: String,
+  pub username: Option<String>,
   pub icons: Option<Icons>,
 }
 
+impl Message {
+  /// Who posted the message, the user id or else the bot's name or id.
+  pub fn author(&self) -> &str {
+    self
+      .user
+      .as_deref()
+      .or(self.username.as_deref())
+      .or(self.bot_id.as_deref())
+      .unwrap_or_default()
+  }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Icons {
   pub emoji: String,
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::error::Error;
+
+  #[test]
+  fn parse_body_api_error() -> Result<(), Box<dyn Error>> {
+    let body = r#"{"ok":false,"error":"channel_not_found"}"#;
+    match parse_body::<Response>(body) {
+      Err(SlackError::Api { error, warning, messages }) => {
+        assert_eq!(ApiError::ChannelNotFound, error);
+        assert!(warning.is_none());
+        assert!(messages.is_empty());
+      }
+      other => panic!("expected api error, got: {other:?}"),
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn parse_body_api_error_warnings() -> Result<(), Box<dyn Error>> {
+    let body = r#"{"ok":false,"error":"invalid_arguments","warning":"missing_charset","response_metadata":{"messages":["[ERROR] missing required field: channel"],"warnings":["missing_charset"]}}"#;
+    match parse_body::<Response>(body) {
+      Err(SlackError::Api { error, warning, messages }) => {
+        assert_eq!(ApiError::Other(String::from("invalid_arguments")), error);
+        assert_eq!(Some("missing_charset"), warning.as_deref());
+        assert_eq!(vec!["[ERROR] missing required field: channel"], messages);
+      }
+      other => panic!("expected api error, got: {other:?}"),
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn parse_body_api_error_without_code() -> Result<(), Box<dyn Error>> {
+    match parse_body::<Response>(r#"{"ok":false}"#) {
+      Err(SlackError::Api { error, .. }) => {
+        assert_eq!("unknown_error", error.code());
+      }
+      other => panic!("expected api error, got: {other:?}"),
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn parse_body_not_json() -> Result<(), Box<dyn Error>> {
+    let actual = parse_body::<Response>("<html></html>");
+    assert!(matches!(actual, Err(SlackError::Decode(_))));
+    Ok(())
+  }
+
+  #[test]
+  fn parse_user_message() -> Result<(), Box<dyn Error>> {
+    let body = r#"{"type":"message","user":"U1","text":"lgtm","ts":"1.3","thread_ts":"1.2"}"#;
+    let actual: Message = serde_json::from_str(body)?;
+    assert_eq!("U1", actual.author());
+    assert!(actual.bot_id.is_none());
+    assert_eq!(Some("1.2"), actual.thread_ts.as_deref());
+    Ok(())
+  }
+
+  #[test]
+  fn parse_body_success_warning() -> Result<(), Box<dyn Error>> {
+    let body = r#"{"ok":true,"channel":"C1","ts":"1.2","warning":"missing_charset","response_metadata":{"warnings":["missing_charset"]},"message":{"type":"message","app_id":"A1","bot_id":"B1","text":"hi","ts":"1.2","username":"bot"}}"#;
+    let actual: Response = parse_body(body)?;
+    assert_eq!("C1", actual.channel);
+    assert_eq!(Some("missing_charset"), actual.warning.as_deref());
+    assert_eq!(
+      vec!["missing_charset"],
+      actual.response_metadata.unwrap().warnings
+    );
+    Ok(())
+  }
+}
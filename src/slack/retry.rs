@@ -0,0 +1,143 @@
+use crate::globals::{
+  CHAT_POST_EPHEMERAL, CHAT_POST_MESSAGE, CHAT_SCHEDULE_MESSAGE,
+  FILES_COMPLETE_UPLOAD_EXTERNAL,
+};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Response as HttpResponse, StatusCode};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// How failed calls are retried, see [`super::Client::retry`].
+///
+/// Rate limited calls wait as long as slack's `Retry-After` asks, server
+/// errors and failed connections back off exponentially with jitter.
+///
+/// A call that got a 2xx or any 4xx other than 429 is never retried. A 5xx
+/// can come from a proxy after slack already acted, so it is only retried
+/// for methods that don't post a new message, see [`posts_message`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retry {
+  /// Retries after the first attempt, 0 turns retrying off.
+  pub max_retries: u32,
+  /// Give up once this much time has passed since the first attempt.
+  pub timeout: Duration,
+  /// Backoff before the first retry, doubled for every one after.
+  pub base_delay: Duration,
+  pub max_delay: Duration,
+}
+
+impl Default for Retry {
+  fn default() -> Self {
+    Retry {
+      max_retries: 3,
+      timeout: Duration::from_secs(60),
+      base_delay: Duration::from_millis(500),
+      max_delay: Duration::from_secs(30),
+    }
+  }
+}
+
+impl Retry {
+  /// How long to wait before retrying `attempt` of a call to `method`, none
+  /// when it is done.
+  pub fn delay(
+    &self, method: &str, attempt: u32,
+    outcome: &Result<HttpResponse, reqwest::Error>,
+  ) -> Option<Duration> {
+    if attempt >= self.max_retries {
+      return None;
+    }
+    match outcome {
+      Ok(res) if res.status() == StatusCode::TOO_MANY_REQUESTS => Some(
+        retry_after(res.headers()).unwrap_or_else(|| self.backoff(attempt)),
+      ),
+      Ok(res) if res.status().is_server_error() && !posts_message(method) => {
+        Some(self.backoff(attempt))
+      }
+      Ok(_) => None,
+      // the request never reached slack, so it can't have been acted on
+      Err(e) if e.is_connect() => Some(self.backoff(attempt)),
+      Err(_) => None,
+    }
+  }
+
+  /// Exponential backoff with "equal jitter", somewhere between half and
+  /// all of the delay so clients that failed together don't retry together.
+  fn backoff(&self, attempt: u32) -> Duration {
+    let exp = 2u32.saturating_pow(attempt);
+    let delay = self.base_delay.saturating_mul(exp).min(self.max_delay);
+    let half = u64::try_from(delay.as_nanos() / 2).unwrap_or(u64::MAX);
+    Duration::from_nanos(half.saturating_add(random() % half.saturating_add(1)))
+  }
+}
+
+/// Whether calling `method` twice posts twice, so it is unsafe to retry once
+/// slack may have acted on it.
+fn posts_message(method: &str) -> bool {
+  matches!(
+    method,
+    CHAT_POST_EPHEMERAL
+      | CHAT_POST_MESSAGE
+      | CHAT_SCHEDULE_MESSAGE
+      | FILES_COMPLETE_UPLOAD_EXTERNAL
+  )
+}
+
+/// Longest `Retry-After` taken at its word, slack asks for seconds to
+/// minutes so anything longer is a broken header or proxy.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60 * 60);
+
+/// Seconds slack asks to wait before calling again, at most
+/// [`MAX_RETRY_AFTER`].
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+  let secs = headers.get(RETRY_AFTER)?.to_str().ok()?.trim().parse().ok()?;
+  Some(Duration::from_secs(secs).min(MAX_RETRY_AFTER))
+}
+
+/// Good enough randomness for jitter without a rand dependency, every
+/// `RandomState` is seeded differently.
+fn random() -> u64 {
+  RandomState::new().build_hasher().finish()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use reqwest::header::HeaderValue;
+
+  #[test]
+  fn backoff_doubles_within_jitter() {
+    let retry = Retry {
+      base_delay: Duration::from_millis(100),
+      max_delay: Duration::from_secs(1),
+      ..Retry::default()
+    };
+    for (attempt, full) in [(0, 100), (1, 200), (2, 400), (3, 800), (6, 1000)] {
+      let actual = retry.backoff(attempt);
+      let full = Duration::from_millis(full);
+      assert!(actual >= full / 2 && actual <= full, "{attempt}: {actual:?}");
+    }
+  }
+
+  #[test]
+  fn posts_message_methods() {
+    assert!(posts_message(CHAT_POST_MESSAGE));
+    assert!(posts_message(FILES_COMPLETE_UPLOAD_EXTERNAL));
+    assert!(!posts_message(crate::globals::CHAT_UPDATE));
+    assert!(!posts_message(crate::globals::USERS_LIST));
+  }
+
+  #[test]
+  fn retry_after_seconds() {
+    let mut headers = HeaderMap::new();
+    assert_eq!(None, retry_after(&headers));
+    headers.insert(RETRY_AFTER, HeaderValue::from_static("30"));
+    assert_eq!(Some(Duration::from_secs(30)), retry_after(&headers));
+    headers
+      .insert(RETRY_AFTER, HeaderValue::from_static("18446744073709551615"));
+    assert_eq!(Some(MAX_RETRY_AFTER), retry_after(&headers));
+    headers.insert(RETRY_AFTER, HeaderValue::from_static("soon"));
+    assert_eq!(None, retry_after(&headers));
+  }
+}
@@ -0,0 +1,274 @@
+use super::lookup::close_matches;
+use super::response::ResponseMetadata;
+use super::{Client, SlackError};
+use crate::globals::{USERS_LIST, USERS_LOOKUP_BY_EMAIL};
+use serde::{Deserialize, Serialize};
+
+/// A workspace member as returned by `users.list` and `users.lookupByEmail`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct User {
+  pub id: String,
+  /// Legacy username, still accepted as an `@handle`.
+  #[serde(default)]
+  pub name: String,
+  #[serde(default)]
+  pub deleted: bool,
+  #[serde(default)]
+  pub profile: Profile,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+  #[serde(default)]
+  pub display_name: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub email: Option<String>,
+  #[serde(default)]
+  pub real_name: String,
+}
+
+impl User {
+  /// Whether `handle`, without the `@`, names this user.
+  fn is_named(&self, handle: &str) -> bool {
+    self.name == handle || self.profile.display_name == handle
+  }
+}
+
+/// Body of a `users.list` call.
+#[derive(Debug, Serialize)]
+struct List<'a> {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  cursor: Option<&'a str>,
+  limit: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListResponse {
+  members: Vec<User>,
+  response_metadata: Option<ResponseMetadata>,
+}
+
+/// Body of a `users.lookupByEmail` call.
+#[derive(Debug, Serialize)]
+struct LookupByEmail<'a> {
+  email: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct UserResponse {
+  user: User,
+}
+
+/// Whether `user` already is a user id rather than a handle or email.
+pub fn is_user_id(user: &str) -> bool {
+  user.len() >= 9
+    && user.starts_with(['U', 'W'])
+    && user.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+}
+
+impl Client {
+  /// Every member of the workspace, including deactivated ones.
+  pub async fn list_users(&self) -> Result<Vec<User>, SlackError> {
+    let mut users = Vec::new();
+    let mut cursor = None;
+    loop {
+      let list = List { cursor: cursor.as_deref(), limit: 200 };
+      let page: ListResponse = self.post_form(USERS_LIST, &list).await?;
+      users.extend(page.members);
+      cursor = ResponseMetadata::next_cursor(page.response_metadata.as_ref());
+      if cursor.is_none() {
+        return Ok(users);
+      }
+    }
+  }
+
+  pub async fn lookup_user_by_email(
+    &self, email: &str,
+  ) -> Result<User, SlackError> {
+    let lookup = LookupByEmail { email };
+    let res: UserResponse =
+      self.post_form(USERS_LOOKUP_BY_EMAIL, &lookup).await?;
+    Ok(res.user)
+  }
+
+  /// Resolve an email, `@handle` or `handle` to a user id, ids are returned
+  /// as is.
+  pub async fn resolve_user(&self, user: &str) -> Result<String, SlackError> {
+    let mut ids = self.resolve_users(&[user]).await?;
+    Ok(ids.remove(0))
+  }
+
+  /// Resolve each of `users` like [`Client::resolve_user`], listing the
+  /// workspace at most once.
+  pub async fn resolve_users(
+    &self, users: &[impl AsRef<str>],
+  ) -> Result<Vec<String>, SlackError> {
+    let mut members = None;
+    let mut ids = Vec::with_capacity(users.len());
+    for user in users.iter().map(AsRef::as_ref) {
+      let id = if is_user_id(user) {
+        user.to_owned()
+      } else if user.get(1..).is_some_and(|rest| rest.contains('@')) {
+        self.lookup_user_by_email(user).await?.id
+      } else {
+        if members.is_none() {
+          members = Some(self.list_users().await?);
+        }
+        let handle = user.strip_prefix('@').unwrap_or(user);
+        find_user(handle, members.as_deref().unwrap_or_default())?
+      };
+      ids.push(id);
+    }
+    Ok(ids)
+  }
+}
+
+/// The id of the one active user named `handle`.
+fn find_user(handle: &str, users: &[User]) -> Result<String, SlackError> {
+  let active = || users.iter().filter(|u| !u.deleted);
+  let mut matches = active().filter(|u| u.is_named(handle));
+  match (matches.next(), matches.next()) {
+    (Some(found), None) => Ok(found.id.clone()),
+    (Some(first), Some(second)) => {
+      let ids = [first, second].into_iter().chain(matches);
+      Err(SlackError::Ambiguous {
+        kind: "user",
+        name: handle.to_owned(),
+        matches: ids.map(|u| u.id.clone()).collect(),
+      })
+    }
+    (None, _) => Err(SlackError::NotFound {
+      kind: "user",
+      name: handle.to_owned(),
+      close_matches: close_matches(
+        handle,
+        active()
+          .flat_map(|u| [u.name.as_str(), &u.profile.display_name])
+          .filter(|name| !name.is_empty()),
+      ),
+    }),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::slack::mock;
+  use crate::slack::ApiError;
+  use std::error::Error;
+  use wiremock::matchers::body_string_contains;
+  use wiremock::MockServer;
+
+  #[test]
+  fn user_ids() {
+    assert!(is_user_id("U12345ABCDE"));
+    assert!(is_user_id("W0123456789"));
+    assert!(!is_user_id("alice"));
+    assert!(!is_user_id("@alice"));
+    assert!(!is_user_id("U123"));
+    assert!(!is_user_id("C12345ABCDE"));
+  }
+
+  async fn setup_mock_server() -> Result<MockServer, Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    let route = "users.list";
+    let page1 = r#"{"ok":true,"members":[{"id":"U0000000001","name":"alice","profile":{"display_name":"Alice"}},{"id":"U0000000002","name":"bob","profile":{"display_name":""}}],"response_metadata":{"next_cursor":"page2"}}"#;
+    let page2 = r#"{"ok":true,"members":[{"id":"U0000000003","name":"bobby","deleted":true},{"id":"U0000000004","name":"sam"},{"id":"U0000000005","name":"sam.2","profile":{"display_name":"sam"}}],"response_metadata":{"next_cursor":""}}"#;
+    mock::given(route)
+      .and(body_string_contains("cursor=page2"))
+      .respond_with(mock::json(page2))
+      .mount(&mock_server)
+      .await;
+    mock::given(route)
+      .and(body_string_contains("limit=200"))
+      .respond_with(mock::json(page1))
+      .mount(&mock_server)
+      .await;
+    Ok(mock_server)
+  }
+
+  #[tokio::test]
+  async fn list_users_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = setup_mock_server().await?;
+    let actual = mock::client(&mock_server).list_users().await?;
+    assert_eq!(5, actual.len());
+    assert!(actual[2].deleted);
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn resolve_user_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = setup_mock_server().await?;
+    let client = mock::client(&mock_server);
+    assert_eq!("U0000000001", client.resolve_user("@alice").await?);
+    assert_eq!("U0000000001", client.resolve_user("Alice").await?);
+    assert_eq!("U0000000002", client.resolve_user("bob").await?);
+    assert_eq!("U9999999999", client.resolve_user("U9999999999").await?);
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn resolve_users_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = setup_mock_server().await?;
+    let actual = mock::client(&mock_server)
+      .resolve_users(&["@alice", "U9999999999", "bob"])
+      .await?;
+    assert_eq!(vec!["U0000000001", "U9999999999", "U0000000002"], actual);
+    let requests = mock_server.received_requests().await.unwrap_or_default();
+    assert_eq!(2, requests.len(), "users.list is paged through once");
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn resolve_user_email_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    let body = r#"{"ok":true,"user":{"id":"U0000000001","name":"alice","profile":{"email":"alice@example.com"}}}"#;
+    mock::given("users.lookupByEmail")
+      .and(body_string_contains("email=alice%40example.com"))
+      .respond_with(mock::json(body))
+      .mount(&mock_server)
+      .await;
+    let actual =
+      mock::client(&mock_server).resolve_user("alice@example.com").await?;
+    assert_eq!("U0000000001", actual);
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn resolve_user_email_not_found_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    let body = r#"{"ok":false,"error":"users_not_found"}"#;
+    mock::mount_json(&mock_server, "users.lookupByEmail", body).await;
+    match mock::client(&mock_server).resolve_user("eve@example.com").await {
+      Err(SlackError::Api { error: ApiError::UsersNotFound, .. }) => {}
+      other => panic!("expected users_not_found, got: {other:?}"),
+    }
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn resolve_user_not_found_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = setup_mock_server().await?;
+    match mock::client(&mock_server).resolve_user("@bobb").await {
+      Err(SlackError::NotFound { kind, name, close_matches }) => {
+        assert_eq!("user", kind);
+        assert_eq!("bobb", name);
+        assert_eq!(vec!["bob"], close_matches);
+      }
+      other => panic!("expected not found error, got: {other:?}"),
+    }
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn resolve_user_ambiguous_mock() -> Result<(), Box<dyn Error>> {
+    let mock_server = setup_mock_server().await?;
+    match mock::client(&mock_server).resolve_user("sam").await {
+      Err(SlackError::Ambiguous { matches, .. }) => {
+        assert_eq!(vec!["U0000000004", "U0000000005"], matches);
+      }
+      other => panic!("expected ambiguous error, got: {other:?}"),
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,116 @@
+//! Incoming webhooks, see <https://api.slack.com/messaging/webhooks>.
+
+use super::{http_client, ApiError, Message, SlackError};
+use reqwest::{header::CONTENT_TYPE, Client as HttpClient};
+
+/// Posts messages to an incoming webhook url instead of the web api.
+///
+/// The webhook decides the channel, a `channel` on the message only
+/// overrides it for legacy webhooks.
+#[derive(Debug, Clone)]
+pub struct Webhook {
+  url: String,
+  http: HttpClient,
+}
+
+impl Webhook {
+  /// # Panics
+  ///
+  /// When the http client can't be built, ex) no usable tls backend.
+  pub fn new(url: impl Into<String>) -> Self {
+    Webhook { url: url.into(), http: http_client() }
+  }
+
+  /// Webhooks answer in plain text, `ok` or an error code like
+  /// `channel_not_found`, and never return the message's timestamp.
+  pub async fn send_message(
+    &self, message: &Message,
+  ) -> Result<(), SlackError> {
+    message.validate()?;
+    let res = self
+      .http
+      .post(&self.url)
+      .header(CONTENT_TYPE, "application/json; charset=utf-8")
+      .json(message)
+      .send()
+      .await?;
+    let status = res.status();
+    let body = res.text().await?;
+    parse(status, body)
+  }
+}
+
+fn parse(status: reqwest::StatusCode, body: String) -> Result<(), SlackError> {
+  let code = body.trim();
+  if status.is_success() && code == "ok" {
+    return Ok(());
+  }
+  let is_code = !code.is_empty()
+    && code.chars().all(|c| c.is_ascii_lowercase() || c == '_');
+  if is_code {
+    return Err(ApiError::from_code(code).into());
+  }
+  Err(SlackError::Status { status, body })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use reqwest::StatusCode;
+  use std::error::Error;
+  use wiremock::matchers::{body_json, method, path};
+  use wiremock::{Mock, MockServer, ResponseTemplate};
+
+  async fn setup_mock_server(
+    response: ResponseTemplate,
+  ) -> Result<MockServer, Box<dyn Error>> {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+      .and(path("/services/T1/B1/secret"))
+      .and(body_json(serde_json::json!({ "text": "deployed" })))
+      .respond_with(response)
+      .mount(&mock_server)
+      .await;
+    Ok(mock_server)
+  }
+
+  fn webhook(mock_server: &MockServer) -> Webhook {
+    Webhook::new(format!("{}/services/T1/B1/secret", mock_server.uri()))
+  }
+
+  #[tokio::test]
+  async fn send_message_mock() -> Result<(), Box<dyn Error>> {
+    let ok = ResponseTemplate::new(200).set_body_string("ok");
+    let mock_server = setup_mock_server(ok).await?;
+    webhook(&mock_server).send_message(&Message::new("", "deployed")).await?;
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn send_message_error_code_mock() -> Result<(), Box<dyn Error>> {
+    let gone = ResponseTemplate::new(404).set_body_string("channel_not_found");
+    let mock_server = setup_mock_server(gone).await?;
+    let msg = Message::new("", "deployed");
+    match webhook(&mock_server).send_message(&msg).await {
+      Err(SlackError::Api { error, .. }) => {
+        assert_eq!(ApiError::ChannelNotFound, error);
+      }
+      other => panic!("expected api error, got: {other:?}"),
+    }
+    Ok(())
+  }
+
+  #[tokio::test]
+  async fn send_message_status_mock() -> Result<(), Box<dyn Error>> {
+    let html = ResponseTemplate::new(502).set_body_string("<html></html>");
+    let mock_server = setup_mock_server(html).await?;
+    let msg = Message::new("", "deployed");
+    match webhook(&mock_server).send_message(&msg).await {
+      Err(SlackError::Status { status, .. }) => {
+        assert_eq!(StatusCode::BAD_GATEWAY, status);
+      }
+      other => panic!("expected status error, got: {other:?}"),
+    }
+    Ok(())
+  }
+}
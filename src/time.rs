@@ -0,0 +1,213 @@
+//! Just enough date handling for `--at`/`--in` without pulling in a date crate.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const DAY: i64 = 86_400;
+
+pub fn now() -> i64 {
+  let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+  i64::try_from(now.as_secs()).unwrap_or(i64::MAX)
+}
+
+/// Parse unix seconds or an RFC 3339 date time into unix seconds.
+pub fn parse_time(s: &str) -> Result<i64, String> {
+  if let Ok(secs) = s.parse::<i64>() {
+    return Ok(secs);
+  }
+  parse_rfc3339(s).ok_or_else(|| {
+    String::from(
+      "expected unix seconds or RFC 3339 like '2025-01-31T17:00:00Z'",
+    )
+  })
+}
+
+/// Parse a duration like '90s', '15m', '2h', '1d' or '1h30m'.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+  let err =
+    || String::from("expected a duration like '90s', '15m', '2h' or '1h30m'");
+  let mut total = 0u64;
+  let mut digits = String::new();
+  for c in s.chars() {
+    if c.is_ascii_digit() {
+      digits.push(c);
+      continue;
+    }
+    let unit = match c {
+      's' => 1,
+      'm' => 60,
+      'h' => 3_600,
+      'd' => 86_400,
+      'w' => 604_800,
+      _ => return Err(err()),
+    };
+    let n: u64 = digits.parse().map_err(|_| err())?;
+    total = n
+      .checked_mul(unit)
+      .and_then(|secs| total.checked_add(secs))
+      .ok_or_else(err)?;
+    digits.clear();
+  }
+  if !digits.is_empty() || total == 0 {
+    return Err(err());
+  }
+  Ok(Duration::from_secs(total))
+}
+
+/// Format unix seconds as RFC 3339 in UTC, ex) '2025-01-31T17:00:00Z'.
+pub fn format_rfc3339(secs: i64) -> String {
+  let (days, rem) = (secs.div_euclid(DAY), secs.rem_euclid(DAY));
+  let (y, m, d) = civil_from_days(days);
+  let (hh, mm, ss) = (rem / 3_600, rem % 3_600 / 60, rem % 60);
+  format!("{y:04}-{m:02}-{d:02}T{hh:02}:{mm:02}:{ss:02}Z")
+}
+
+fn parse_rfc3339(s: &str) -> Option<i64> {
+  let (date, rest) = s.split_once(['T', 't', ' '])?;
+  let mut date = date.splitn(3, '-');
+  let y: i64 = digits(date.next()?, 4)?;
+  let m: u32 = digits(date.next()?, 2)?;
+  let d: u32 = digits(date.next()?, 2)?;
+
+  let (time, offset) = match rest.find(['Z', 'z', '+', '-']) {
+    Some(i) => rest.split_at(i),
+    None => return None,
+  };
+  let mut time = time.splitn(3, ':');
+  let hh: i64 = digits(time.next()?, 2)?;
+  let mm: i64 = digits(time.next()?, 2)?;
+  // fractional seconds are dropped, slack only takes whole seconds
+  let ss: i64 = digits(time.next()?.split('.').next()?, 2)?;
+  if !(1..=12).contains(&m) || d == 0 || d > days_in_month(y, m) {
+    return None;
+  }
+  if hh > 23 || mm > 59 || ss > 60 {
+    return None;
+  }
+
+  let offset = match offset {
+    "Z" | "z" => 0,
+    _ => {
+      let sign = if offset.starts_with('-') { -1 } else { 1 };
+      let (oh, om) = offset[1..].split_once(':')?;
+      let (oh, om): (i64, i64) = (digits(oh, 2)?, digits(om, 2)?);
+      if oh > 23 || om > 59 {
+        return None;
+      }
+      sign * (oh * 3_600 + om * 60)
+    }
+  };
+  days_from_civil(y, m, d)
+    .checked_mul(DAY)?
+    .checked_add(hh * 3_600 + mm * 60 + ss - offset)
+}
+
+/// Parse `s` only when it is exactly `len` ascii digits, ex) a 4 digit year.
+fn digits<T: std::str::FromStr>(s: &str, len: usize) -> Option<T> {
+  if s.len() != len || !s.bytes().all(|b| b.is_ascii_digit()) {
+    return None;
+  }
+  s.parse().ok()
+}
+
+fn days_in_month(y: i64, m: u32) -> u32 {
+  match m {
+    2 if y % 4 == 0 && (y % 100 != 0 || y % 400 == 0) => 29,
+    2 => 28,
+    4 | 6 | 9 | 11 => 30,
+    _ => 31,
+  }
+}
+
+// http://howardhinnant.github.io/date_algorithms.html
+fn days_from_civil(y: i64, m: u32, d: u32) -> i64 {
+  let y = if m <= 2 { y - 1 } else { y };
+  let era = y.div_euclid(400);
+  let yoe = y - era * 400;
+  let m = i64::from(m);
+  let doy = (153 * (m + if m > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(d) - 1;
+  let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+  era * 146_097 + doe - 719_468
+}
+
+fn civil_from_days(z: i64) -> (i64, i64, i64) {
+  let z = z + 719_468;
+  let era = z.div_euclid(146_097);
+  let doe = z - era * 146_097;
+  let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+  let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+  let mp = (5 * doy + 2) / 153;
+  let d = doy - (153 * mp + 2) / 5 + 1;
+  let m = if mp < 10 { mp + 3 } else { mp - 9 };
+  let y = yoe + era * 400 + i64::from(m <= 2);
+  (y, m, d)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::error::Error;
+
+  #[test]
+  fn parse_time_unix() -> Result<(), Box<dyn Error>> {
+    assert_eq!(1_734_376_519, parse_time("1734376519")?);
+    Ok(())
+  }
+
+  #[test]
+  fn parse_time_rfc3339() -> Result<(), Box<dyn Error>> {
+    assert_eq!(0, parse_time("1970-01-01T00:00:00Z")?);
+    assert_eq!(1_738_342_800, parse_time("2025-01-31T17:00:00Z")?);
+    assert_eq!(1_738_342_800, parse_time("2025-01-31T18:00:00.250+01:00")?);
+    assert_eq!(1_738_342_800, parse_time("2025-01-31T12:00:00-05:00")?);
+    assert_eq!(951_782_400, parse_time("2000-02-29T00:00:00Z")?);
+    assert_eq!(1_709_164_800, parse_time("2024-02-29T00:00:00Z")?);
+    Ok(())
+  }
+
+  #[test]
+  fn parse_time_invalid() {
+    for s in [
+      "",
+      "tomorrow",
+      "2025-01-31",
+      "2025-13-01T00:00:00Z",
+      "2025-01-31T17:00:00",
+      "2025-02-31T00:00:00Z",
+      "2025-02-29T00:00:00Z",
+      "2025-04-31T00:00:00Z",
+      "9000000000000000-01-01T00:00:00Z",
+      "25-01-31T17:00:00Z",
+      "2025-1-31T17:00:00Z",
+      "2025-01-31T17:00:00+99999999999999:00",
+    ] {
+      assert!(parse_time(s).is_err(), "{s}");
+    }
+  }
+
+  #[test]
+  fn parse_duration_units() -> Result<(), Box<dyn Error>> {
+    assert_eq!(Duration::from_secs(90), parse_duration("90s")?);
+    assert_eq!(Duration::from_secs(900), parse_duration("15m")?);
+    assert_eq!(Duration::from_secs(7_200), parse_duration("2h")?);
+    assert_eq!(Duration::from_secs(5_400), parse_duration("1h30m")?);
+    assert_eq!(Duration::from_secs(86_400), parse_duration("1d")?);
+    Ok(())
+  }
+
+  #[test]
+  fn parse_duration_invalid() {
+    for s in ["", "2", "h", "2x", "0m", "-1h"] {
+      assert!(parse_duration(s).is_err(), "{s}");
+    }
+  }
+
+  #[test]
+  fn format_rfc3339_round_trip() -> Result<(), Box<dyn Error>> {
+    for s in
+      ["1970-01-01T00:00:00Z", "2000-02-29T23:59:59Z", "2025-01-31T17:00:00Z"]
+    {
+      assert_eq!(s, format_rfc3339(parse_time(s)?));
+    }
+    Ok(())
+  }
+}
@@ -3,13 +3,15 @@ mod tests {
   use assert_cmd::Command;
   use regex::Regex;
   use std::time::{SystemTime, UNIX_EPOCH};
+  use wiremock::matchers::{body_json, header, method, path};
+  use wiremock::{Mock, MockServer, ResponseTemplate};
 
   const SLACK_MESSAGE: &str = "slack-message";
   const TEST_ICON: &str = ":test:";
 
-  fn parse_timestamp(v: Vec<u8>) -> String {
-    let r = Regex::new(r"[+-]?([0-9]*[.])?[0-9]+").unwrap();
-    let s = str::from_utf8(v.as_slice()).unwrap();
+  fn parse_timestamp(v: &[u8]) -> String {
+    let r = Regex::new("[+-]?([0-9]*[.])?[0-9]+").unwrap();
+    let s = str::from_utf8(v).unwrap();
     r.find(s).unwrap().as_str().to_string()
   }
 
@@ -23,7 +25,7 @@ mod tests {
     let assert = cmd.arg(msg).arg("--icon").arg(TEST_ICON).assert();
 
     let cmd_output = assert.get_output().stdout.clone();
-    let ts = parse_timestamp(cmd_output);
+    let ts = parse_timestamp(&cmd_output);
     let expected_output = format!("Message sent, timestamp: {ts}\n");
     assert.success().stdout(expected_output);
   }
@@ -44,7 +46,7 @@ mod tests {
       .assert();
 
     let cmd_output = assert.get_output().stdout.clone();
-    let ts = parse_timestamp(cmd_output);
+    let ts = parse_timestamp(&cmd_output);
     let expected_output = format!("Message sent, timestamp: {ts}\n");
     assert.success().stdout(expected_output);
   }
@@ -58,7 +60,7 @@ mod tests {
     let assert_cmd1 =
       cmd1.arg(msg).arg("--icon").arg(TEST_ICON).assert().success();
     let output_stdout = assert_cmd1.get_output().stdout.clone();
-    let cmd1_ts = parse_timestamp(output_stdout);
+    let cmd1_ts = parse_timestamp(&output_stdout);
 
     let reply_msg = format!("integration test reply message reply {now:?}");
     let mut cmd2 = Command::cargo_bin(SLACK_MESSAGE).unwrap();
@@ -70,7 +72,7 @@ mod tests {
       .arg(cmd1_ts)
       .assert();
 
-    let cmd2_ts = parse_timestamp(assert.get_output().stdout.clone());
+    let cmd2_ts = parse_timestamp(&assert.get_output().stdout);
     let expected_stdout = format!("Message sent, timestamp: {}\n", cmd2_ts);
     assert.success().stdout(expected_stdout);
     //chris TODO extract timestamp from stdout str
@@ -80,8 +82,144 @@ mod tests {
   #[ignore]
   fn no_message_provided() {
     let mut cmd = Command::cargo_bin(SLACK_MESSAGE).unwrap();
-    let assert = cmd.assert();
-    let expected_out = "error: the following required arguments were not provided:\n  <MESSAGE>\n\nUsage: slack-message <MESSAGE>\n\nFor more information, try '--help'.\n";
+    let assert = cmd.write_stdin("").assert();
+    let expected_out = "Error: message is empty\n";
     assert.failure().code(2).stderr(expected_out);
   }
+
+  #[test]
+  #[ignore]
+  fn send_message_from_stdin() {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let mut cmd = Command::cargo_bin(SLACK_MESSAGE).unwrap();
+
+    let msg = format!("integration test stdin message {now:?}\n");
+    let assert = cmd.arg("--icon").arg(TEST_ICON).write_stdin(msg).assert();
+
+    let cmd_output = assert.get_output().stdout.clone();
+    let ts = parse_timestamp(&cmd_output);
+    let expected_output = format!("Message sent, timestamp: {ts}\n");
+    assert.success().stdout(expected_output);
+  }
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn send_message_api_base_mock() {
+    let mock_server = MockServer::start().await;
+    let body = r#"{"ok":true,"channel":"C1","ts":"1734376519.228539","message":{"type":"message","app_id":"A1","bot_id":"B1","text":"hi","ts":"1734376519.228539","username":"bot"}}"#;
+    Mock::given(method("POST"))
+      .and(path("/api/chat.postMessage"))
+      .and(header("authorization", "Bearer test-token"))
+      .and(body_json(
+        serde_json::json!({ "channel": "C0000000001", "text": "hi" }),
+      ))
+      .respond_with(
+        ResponseTemplate::new(200).set_body_raw(body, "application/json"),
+      )
+      .mount(&mock_server)
+      .await;
+
+    let mut cmd = Command::cargo_bin(SLACK_MESSAGE).unwrap();
+    let assert = cmd
+      .env_remove("SLACK_MESSAGE_WEBHOOK")
+      .env("SLACK_MESSAGE_API_BASE", format!("{}/api", mock_server.uri()))
+      .args(["--auth-token", "test-token", "--channel", "C0000000001", "hi"])
+      .assert();
+    assert.success().stdout("Message sent, timestamp: 1734376519.228539\n");
+
+    let mut cmd = Command::cargo_bin(SLACK_MESSAGE).unwrap();
+    let assert = cmd
+      .env_remove("SLACK_MESSAGE_WEBHOOK")
+      .env("SLACK_MESSAGE_API_BASE", format!("{}/api", mock_server.uri()))
+      .args(["--auth-token", "test-token", "--channel", "C0000000001", "hi"])
+      .args(["--output", "ts-only"])
+      .assert();
+    assert.success().stdout("1734376519.228539\n");
+  }
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn send_message_permalink_mock() {
+    let mock_server = MockServer::start().await;
+    let sent = r#"{"ok":true,"channel":"C0000000001","ts":"1734376519.228539","message":{"type":"message","app_id":"A1","bot_id":"B1","text":"hi","ts":"1734376519.228539","username":"bot"}}"#;
+    let link = r#"{"ok":true,"channel":"C0000000001","permalink":"https://example.slack.com/archives/C0000000001/p1734376519228539"}"#;
+    for (route, body) in
+      [("/api/chat.postMessage", sent), ("/api/chat.getPermalink", link)]
+    {
+      Mock::given(method("POST"))
+        .and(path(route))
+        .respond_with(
+          ResponseTemplate::new(200).set_body_raw(body, "application/json"),
+        )
+        .mount(&mock_server)
+        .await;
+    }
+
+    let mut cmd = Command::cargo_bin(SLACK_MESSAGE).unwrap();
+    let assert = cmd
+      .env_remove("SLACK_MESSAGE_WEBHOOK")
+      .env("SLACK_MESSAGE_API_BASE", format!("{}/api", mock_server.uri()))
+      .args(["--auth-token", "test-token", "--channel", "C0000000001", "hi"])
+      .args(["--permalink", "--output", "ts-only"])
+      .assert();
+    assert.success().stdout(
+      "1734376519.228539\thttps://example.slack.com/archives/C0000000001/p1734376519228539\n",
+    );
+  }
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn fan_out_output_mock() {
+    let mock_server = MockServer::start().await;
+    let sent = r#"{"ok":true,"channel":"C0000000001","ts":"1734376519.228539","message":{"type":"message","text":"hi","ts":"1734376519.228539"}}"#;
+    let failed = r#"{"ok":false,"error":"not_in_channel"}"#;
+    for (channel, body) in [("C0000000001", sent), ("C0000000002", failed)] {
+      Mock::given(method("POST"))
+        .and(path("/api/chat.postMessage"))
+        .and(body_json(serde_json::json!({ "channel": channel, "text": "hi" })))
+        .respond_with(
+          ResponseTemplate::new(200).set_body_raw(body, "application/json"),
+        )
+        .mount(&mock_server)
+        .await;
+    }
+
+    let run = |output: &str| {
+      let mut cmd = Command::cargo_bin(SLACK_MESSAGE).unwrap();
+      cmd
+        .env_remove("SLACK_MESSAGE_WEBHOOK")
+        .env("SLACK_MESSAGE_API_BASE", format!("{}/api", mock_server.uri()))
+        .args(["--auth-token", "test-token", "hi", "--output", output])
+        .args(["-c", "C0000000002", "-c", "C0000000001"])
+        .assert()
+        .failure()
+        .code(8)
+    };
+    run("ts-only").stdout("C0000000001\t1734376519.228539\n");
+    let assert = run("json");
+    let stdout = str::from_utf8(&assert.get_output().stdout).unwrap();
+    let lines: Vec<serde_json::Value> =
+      stdout.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+    assert_eq!(2, lines.len());
+    assert_eq!(serde_json::json!(false), lines[0]["ok"]);
+    assert_eq!("C0000000002", lines[0]["channel"]);
+    assert_eq!("C0000000001", lines[1]["channel"]);
+    assert_eq!("1734376519.228539", lines[1]["ts"]);
+  }
+
+  #[tokio::test(flavor = "multi_thread")]
+  async fn env_webhook_permalink_mock() {
+    let mock_server = MockServer::start().await;
+    Mock::given(method("POST"))
+      .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+      .expect(0)
+      .mount(&mock_server)
+      .await;
+
+    let mut cmd = Command::cargo_bin(SLACK_MESSAGE).unwrap();
+    let assert = cmd
+      .env("SLACK_MESSAGE_WEBHOOK", format!("{}/hook", mock_server.uri()))
+      .args(["--permalink", "hi"])
+      .assert();
+    assert.failure().code(2).stderr(
+      "Error: a webhook can only send messages, set a token for anything else\n",
+    );
+  }
 }